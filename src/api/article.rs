@@ -2,36 +2,47 @@
 //!
 //! 这个模块提供了与文章相关的 API 操作，包括发布、更新、查询、点赞、感谢、收藏、关注、打赏、获取在线人数和 WebSocket 监听等功能。
 //! 主要结构体是 `Article`，用于管理文章相关的 HTTP 请求和 WebSocket 连接。
-//! 事件通过 `ArticleListener` 回调处理，支持实时消息监听。
+//! 事件解析为 [`ArticleEvent`] 后通过 `ArticleListener` 回调处理，支持实时消息监听。
 //!
 //! # 主要组件
 //!
 //! - [`Article`] - 文章客户端结构体，负责所有文章相关的 API 调用和 WebSocket 连接。
 //! - [`ArticleMessageHandler`] - 文章消息处理器，实现 `MessageHandler` trait，处理 WebSocket 消息并异步调用回调。
+//! - [`ArticleEvent`] - 文章频道事件，包括新评论、在线人数变化等，未知消息保留原始 JSON。
 //! - [`ArticleListener`] - 文章监听器类型别名，定义异步监听器函数的签名，用于处理接收到的消息，支持多线程共享。
 //!
 //! # 方法列表
 //!
 //! - [`Article::new`] - 创建新的文章客户端实例。
+//! - [`Article::with_session`] - 使用共享的登录会话创建实例。
 //! - [`Article::post_article`] - 发布新文章。
 //! - [`Article::update_article`] - 更新现有文章。
+//! - [`Article::patch`] - 局部更新文章，仅修改指定字段。
+//! - [`Article::delete`] - 删除自己发布的文章。
 //! - [`Article::list`] - 查询文章列表（支持类型、标签、分页）。
+//! - [`Article::list_before`] - 以文章 Id 为游标查询文章列表。
+//! - [`Article::list_stream`] - 以异步流的形式逐页查询文章列表。
 //! - [`Article::list_by_user`] - 查询指定用户的文章列表。
 //! - [`Article::detail`] - 获取文章详情（包括评论分页）。
+//! - [`Article::raw_content`] - 获取文章的 Markdown 原文。
 //! - [`Article::vote`] - 点赞或点踩文章。
-//! - [`Article::thank`] - 感谢文章。
+//! - [`Article::thank`] - 感谢文章，返回最新感谢数。
 //! - [`Article::follow`] - 收藏或取消收藏文章。
 //! - [`Article::watch`] - 关注或取消关注文章。
 //! - [`Article::reward`] - 打赏文章。
 //! - [`Article::heat`] - 获取文章在线人数。
-//! - [`Article::add_listener`] - 添加文章 WebSocket 监听器。
+//! - [`Article::trending`] - 获取热门文章及其当前在线人数。
+//! - [`Article::add_listener`] - 添加文章 WebSocket 监听器，连接由调用方持有并负责关闭。
+//! - [`Article::listen`] - 监听文章频道，连接由 `Article` 管理。
+//! - [`Article::off`] - 停止监听指定文章。
+//! - [`Article::listening`] - 获取正在监听的文章 id。
+//! - [`Article::disconnect`] - 关闭所有文章频道连接。
 //!
 //! # 示例
 //!
 //! ```rust,no_run
-//! use fishpi_sdk::api::article::{Article, ArticleListener};
-//! use fishpi_sdk::model::article::{ArticlePost, ArticleType};
-//! use serde_json::Value;
+//! use fishpi_sdk::api::article::{Article, ArticleEvent, ArticleListener};
+//! use fishpi_sdk::model::article::{ArticlePost, ArticleType, EditorType};
 //! use std::sync::Arc;
 //!
 //! #[tokio::main]
@@ -50,39 +61,109 @@
 //!         rewardPoint: None,
 //!         anonymous: None,
 //!         offerPoint: None,
+//!         editorType: EditorType::Markdown,
 //!     };
 //!     let article_id = article.post_article(&data).await?;
 //!     let detail = article.detail(&article_id, 1).await?;
 //!     println!("Article title: {}", detail.title);
 //!
-//!     let callback: ArticleListener = Arc::new(|msg: Value| {
+//!     let callback: ArticleListener = Arc::new(|event: ArticleEvent| {
 //!         Box::pin(async move {
-//!             println!("Received message: {:?}", msg);
+//!             if let ArticleEvent::CommentAdded(comment) = event {
+//!                 println!("New comment: {}", comment.content);
+//!             }
 //!         })
 //!     });
-//!     let _ws_client = article
-//!         .add_listener(&article_id, ArticleType::Normal, Arc::clone(&callback))
+//!     article
+//!         .listen(&article_id, ArticleType::Normal, Arc::clone(&callback))
 //!         .await?;
 //!
+//!     // 不再需要时关闭连接
+//!     article.off(&article_id);
+//!
 //!     Ok(())
 //! }
 //! ```
-use std::{pin::Pin, sync::Arc};
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    sync::{Arc, Mutex as StdMutex},
+};
 
-use serde_json::{Value, json};
+use futures_util::{Stream, StreamExt, stream};
+use serde::Serialize;
+use serde_json::Value;
 
 use crate::{
+    api::session::Session,
     api::ws::{MessageHandler, WebSocketClient, build_ws_url},
     model::article::{
-        ArticleDetail, ArticleList, ArticleListType, ArticlePost, ArticleType, Pagination,
+        ArticleComment, ArticleDetail, ArticleList, ArticleListType, ArticlePatch, ArticlePost,
+        ArticleType, Pagination, ThankResult, VoteStatus,
     },
     model::reaction::ReactionMutationResult,
-    utils::{ResponseResult, build_http_path, error::Error, get, post},
+    utils::{
+        EmptyBody, ResponseResult, build_http_path, error::Error, get, get_text, post,
+        post_with_key, strip_raw_comment, ws_domain,
+    },
 };
 
+/// [`Article::trending`] 查询在线人数的最大并发数
+pub const TRENDING_CONCURRENCY: usize = 4;
+
+/// [`Article::list_before`] 最多翻阅的页数
+pub const LIST_BEFORE_MAX_PAGES: u32 = 10;
+
+/// [`Article::list_before`] 单次最多返回的文章数
+pub const LIST_BEFORE_MAX_SIZE: u32 = 100;
+
+/// 文章投票请求体
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VoteBody<'a> {
+    data_id: &'a str,
+}
+
+/// 收藏/关注文章请求体
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FollowBody<'a> {
+    following_id: &'a str,
+}
+
+/// 文章频道事件
+#[derive(Clone, Debug)]
+pub enum ArticleEvent {
+    /// 新评论
+    CommentAdded(Box<ArticleComment>),
+    /// 文章在线人数变化
+    HeatChanged(u32),
+    /// 未识别的消息，保留原始数据
+    Raw(Value),
+}
+
+impl ArticleEvent {
+    /// 解析文章频道消息，无法识别的消息返回 [`ArticleEvent::Raw`]
+    ///
+    /// - `data` 消息 JSON
+    pub fn from_value(data: Value) -> Self {
+        match data["type"].as_str() {
+            Some("comment") => match ArticleComment::from_value(&data) {
+                Ok(comment) => ArticleEvent::CommentAdded(Box::new(comment)),
+                Err(_) => ArticleEvent::Raw(data),
+            },
+            Some("articleHeat") => match data["articleHeat"].as_u64() {
+                Some(heat) => ArticleEvent::HeatChanged(heat as u32),
+                None => ArticleEvent::Raw(data),
+            },
+            _ => ArticleEvent::Raw(data),
+        }
+    }
+}
+
 /// 文章监听器类型
 pub type ArticleListener =
-    Arc<dyn Fn(Value) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync + 'static>;
+    Arc<dyn Fn(ArticleEvent) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync + 'static>;
 
 /// 文章消息处理器
 pub struct ArticleMessageHandler {
@@ -100,22 +181,37 @@ impl MessageHandler for ArticleMessageHandler {
         let callback = Arc::clone(&self.callback);
         let msg = msg.clone();
         tokio::spawn(async move {
-            if let Ok(json) = serde_json::from_str::<Value>(&msg) {
-                callback(json).await;
-            } else {
-                callback(Value::String(msg)).await;
-            }
+            let event = match serde_json::from_str::<Value>(&msg) {
+                Ok(json) => ArticleEvent::from_value(json),
+                Err(_) => ArticleEvent::Raw(Value::String(msg)),
+            };
+            callback(event).await;
         });
     }
 }
 
 pub struct Article {
-    api_key: String,
+    session: Arc<Session>,
+    /// 通过 [`Article::listen`] 建立的连接，按文章 id 保存
+    listeners: StdMutex<HashMap<String, WebSocketClient>>,
 }
 
 impl Article {
     pub fn new(api_key: String) -> Self {
-        Self { api_key }
+        Self::with_session(Session::new(api_key))
+    }
+
+    /// 使用共享的登录会话创建实例，API key 从会话读取
+    pub fn with_session(session: Arc<Session>) -> Self {
+        Self {
+            session,
+            listeners: StdMutex::new(HashMap::new()),
+        }
+    }
+
+    /// 重新设置 API key，共享同一会话的模块一并生效
+    pub fn set_api_key(&mut self, api_key: String) {
+        self.session.set_api_key(api_key);
     }
 
     /// 给帖子添加/切换/取消 emoji reaction。
@@ -126,7 +222,7 @@ impl Article {
         article_id: &str,
         value: &str,
     ) -> Result<ReactionMutationResult, Error> {
-        crate::api::reaction::Reaction::new(self.api_key.clone())
+        crate::api::reaction::Reaction::new(self.session.api_key())
             .article(article_id, value)
             .await
     }
@@ -139,20 +235,15 @@ impl Article {
     pub async fn post_article(&self, data: &ArticlePost) -> Result<String, Error> {
         let url = "article".to_string();
 
-        let mut data_json = data.to_json()?;
-        data_json["apiKey"] = Value::String(self.api_key.clone());
-
-        let resp = post(&url, Some(data_json)).await?;
+        let resp = post_with_key(&url, data, &self.session.api_key()).await?;
 
         if resp.get("code").and_then(|c| c.as_i64()).unwrap_or(-1) != 0 {
-            return Err(Error::Api(
-                resp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+            return Err(Error::from_response(&resp, "API error"));
         }
 
         let article_id = resp["articleId"]
             .as_str()
-            .ok_or_else(|| Error::Api("Missing articleId in response".to_string()))?
+            .ok_or_else(|| Error::api("Missing articleId in response".to_string()))?
             .to_string();
 
         Ok(article_id)
@@ -167,25 +258,69 @@ impl Article {
     pub async fn update_article(&self, id: &str, data: &ArticlePost) -> Result<String, Error> {
         let url = format!("article/{}", id);
 
-        let mut data_json = data.to_json()?;
-        data_json["apiKey"] = Value::String(self.api_key.clone());
-
-        let resp = post(&url, Some(data_json)).await?;
+        let resp = post_with_key(&url, data, &self.session.api_key()).await?;
 
         if resp.get("code").and_then(|c| c.as_i64()).unwrap_or(-1) != 0 {
-            return Err(Error::Api(
-                resp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+            return Err(Error::from_response(&resp, "API error"));
         }
 
         let article_id = resp["articleId"]
             .as_str()
-            .ok_or_else(|| Error::Api("Missing articleId in response".to_string()))?
+            .ok_or_else(|| Error::api("Missing articleId in response".to_string()))?
             .to_string();
 
         Ok(article_id)
     }
 
+    /// 删除自己发布的文章
+    ///
+    /// 服务端只允许作者删除，且已有评论、打赏等互动的文章不能删除，此时返回服务端的错误信息。
+    ///
+    /// - `id` 文章 Id
+    ///
+    /// 返回执行结果
+    pub async fn delete(&self, id: &str) -> Result<ResponseResult, Error> {
+        let url = format!("article/{}/remove", id);
+
+        let rsp = post_with_key(&url, &EmptyBody {}, &self.session.api_key()).await?;
+
+        let failed = rsp.get("code").and_then(|c| c.as_i64()).unwrap_or(0) != 0
+            || rsp.get("sc").and_then(|sc| sc.as_bool()) == Some(false);
+        if failed {
+            return Err(Error::from_response(&rsp, "Failed to delete article"));
+        }
+
+        ResponseResult::from_value(&rsp)
+    }
+
+    /// 局部更新文章
+    ///
+    /// 服务端只支持整篇更新，这里先获取文章详情，合并 `changes` 中设置的字段后再提交。
+    /// 未修改正文时，详情中没有 Markdown 原文则通过 [`Article::raw_content`] 获取，
+    /// 仍取不到原文时返回 [Error::Parse]，不会把渲染后的 HTML 当作正文提交。
+    ///
+    /// - `id` 文章id
+    /// - `changes` 需要修改的字段
+    ///
+    /// 返回文章id
+    pub async fn patch(&self, id: &str, changes: &ArticlePatch) -> Result<String, Error> {
+        let mut detail = self.detail(id, 1).await?;
+        if changes.is_empty() {
+            return Ok(detail.oId);
+        }
+
+        if let Some(content) = &changes.content {
+            detail.source = content.clone();
+        } else if !detail.has_markdown_source() {
+            detail.source = self.raw_content(id).await?;
+        }
+
+        let mut data = ArticlePost::from_detail(&detail)?;
+        changes.apply(&mut data);
+
+        self.update_article(id, &data).await
+    }
+
     /// 查询文章列表
     ///
     /// * `type` 查询类型，来自 [ArticleListType]
@@ -205,7 +340,7 @@ impl Article {
             (Some(tag), ArticleListType::Long) => format!("api/articles/tag/{}", tag),
             (Some(tag), _) => format!("api/articles/tag/{}{}", tag, type_.to_code()),
             (None, ArticleListType::Perfect) => {
-                return Err(Error::Api(
+                return Err(Error::api(
                     "优选帖子列表需要指定标签，请使用 /api/articles/tag/<标签URI>/perfect"
                         .to_string(),
                 ));
@@ -218,21 +353,110 @@ impl Article {
             &[
                 ("p", page.to_string()),
                 ("size", size.to_string()),
-                ("apiKey", self.api_key.clone()),
+                ("apiKey", self.session.api_key()),
             ],
         );
 
         let rsp = get(&url).await?;
 
         if rsp.get("code").and_then(|c| c.as_i64()).unwrap_or(-1) != 0 {
-            return Err(Error::Api(
-                rsp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+            return Err(Error::from_response(&rsp, "API error"));
         }
 
         ArticleList::from_value(&rsp["data"])
     }
 
+    /// 以异步流的形式逐页查询文章列表
+    ///
+    /// 从第一页开始按需翻页，到达 `paginationPageCount` 或某页为空时结束。
+    /// 请求失败时产生一个错误并结束。
+    ///
+    /// * `type` 查询类型，来自 [ArticleListType]
+    /// * `tag` 指定查询标签（可选）
+    /// * `size` 每页数量
+    ///
+    /// 返回文章流
+    pub fn list_stream(
+        &self,
+        type_: ArticleListType,
+        tag: Option<String>,
+        size: u32,
+    ) -> impl Stream<Item = Result<ArticleDetail, Error>> + use<> {
+        let session = Arc::clone(&self.session);
+        stream::unfold(Some(1), move |page| {
+            let article = Article::with_session(Arc::clone(&session));
+            let type_ = type_.clone();
+            let tag = tag.clone();
+            async move {
+                let page = page?;
+                match article.list(type_, page, size, tag.as_deref()).await {
+                    Ok(list) => {
+                        let next_page = (!list.list.is_empty() && page < list.pagination.count)
+                            .then_some(page + 1);
+                        Some((list.list.into_iter().map(Ok).collect(), next_page))
+                    }
+                    Err(e) => Some((vec![Err(e)], None)),
+                }
+            }
+        })
+        .flat_map(stream::iter)
+    }
+
+    /// 以文章 Id 为游标查询文章列表
+    ///
+    /// 服务端只提供页码分页，这里从第一页开始翻页，只保留 Id 小于游标的文章，
+    /// 避免翻页期间有新文章发布导致的重复或遗漏。文章 Id 为创建时间戳，
+    /// 因此游标适用于按发布时间排序的列表（如 [ArticleListType::Recent]）。
+    /// 最多翻阅 [`LIST_BEFORE_MAX_PAGES`] 页，翻到上限时返回已找到的文章，可能不足 `size` 篇。
+    ///
+    /// * `type` 查询类型，来自 [ArticleListType]
+    /// * `before` 游标文章 Id，返回比它更早的文章
+    /// * `size` 返回数量，最大 [`LIST_BEFORE_MAX_SIZE`]
+    ///
+    /// 返回文章列表，空列表表示已到列表末尾；游标过旧、翻到上限仍没有更早的文章时返回 [Error::Api]
+    pub async fn list_before(
+        &self,
+        type_: ArticleListType,
+        before: &str,
+        size: u32,
+    ) -> Result<Vec<ArticleDetail>, Error> {
+        let before = before
+            .parse::<u64>()
+            .map_err(|e| Error::Parse(format!("Invalid article id {}: {}", before, e)))?;
+
+        let size = size.min(LIST_BEFORE_MAX_SIZE);
+        let mut articles = Vec::new();
+        let mut reached_end = false;
+        for page in 1..=LIST_BEFORE_MAX_PAGES {
+            if articles.len() >= size as usize {
+                break;
+            }
+            let list = self.list(type_.clone(), page, size, None).await?;
+            if list.list.is_empty() {
+                reached_end = true;
+                break;
+            }
+            articles.extend(
+                list.list
+                    .into_iter()
+                    .filter(|a| a.oId.parse::<u64>().is_ok_and(|id| id < before)),
+            );
+            if page >= list.pagination.count {
+                reached_end = true;
+                break;
+            }
+        }
+
+        if articles.is_empty() && size > 0 && !reached_end {
+            return Err(Error::api(format!(
+                "文章 {} 之前的内容超出翻页上限 {} 页",
+                before, LIST_BEFORE_MAX_PAGES
+            )));
+        }
+        articles.truncate(size as usize);
+        Ok(articles)
+    }
+
     /// 查询文章列表
     ///
     /// - `user` 指定用户
@@ -251,16 +475,14 @@ impl Article {
             &[
                 ("p", page.to_string()),
                 ("size", size.to_string()),
-                ("apiKey", self.api_key.clone()),
+                ("apiKey", self.session.api_key()),
             ],
         );
 
         let rsp = get(&url).await?;
 
         if rsp.get("code").and_then(|c| c.as_i64()).unwrap_or(-1) != 0 {
-            return Err(Error::Api(
-                rsp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+            return Err(Error::from_response(&rsp, "API error"));
         }
 
         ArticleList::from_value(&rsp["data"])
@@ -275,15 +497,13 @@ impl Article {
     pub async fn detail(&self, id: &str, p: u32) -> Result<ArticleDetail, Error> {
         let url = build_http_path(
             &format!("api/article/{}", id),
-            &[("p", p.to_string()), ("apiKey", self.api_key.clone())],
+            &[("p", p.to_string()), ("apiKey", self.session.api_key())],
         );
 
         let rsp = get(&url).await?;
 
         if rsp.get("code").and_then(|c| c.as_i64()).unwrap_or(-1) != 0 {
-            return Err(Error::Api(
-                rsp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+            return Err(Error::from_response(&rsp, "API error"));
         }
 
         let data = &rsp["data"];
@@ -294,48 +514,55 @@ impl Article {
         Ok(article_detail)
     }
 
+    /// 获取文章的 Markdown 原文，可用于编辑时填充内容
+    ///
+    /// - `id` 文章id
+    ///
+    /// 返回 Markdown 原文
+    pub async fn raw_content(&self, id: &str) -> Result<String, Error> {
+        let resp = get_text(&format!("article/{}/raw", id)).await?;
+
+        Ok(strip_raw_comment(&resp))
+    }
+
     /// 点赞/取消点赞文章
     ///
     /// - `id` 文章id
     /// - `like` 点赞类型，true 为点赞，false 为点踩
     ///
-    /// 返回文章点赞状态，true 为点赞，false 为点踩
-    pub async fn vote(&self, id: &str, like: bool) -> Result<bool, Error> {
+    /// 返回投票后的状态 [VoteStatus]，重复同一种投票会取消，返回 [VoteStatus::Normal]
+    pub async fn vote(&self, id: &str, like: bool) -> Result<VoteStatus, Error> {
         let url = format!("vote/{}/article", if like { "up" } else { "down" });
 
-        let data = json!({
-            "dataId": id,
-            "apiKey": self.api_key,
-        });
+        let data = VoteBody { data_id: id };
 
-        let rsp = post(&url, Some(data)).await?;
+        let rsp = post_with_key(&url, &data, &self.session.api_key()).await?;
 
         if rsp.get("code").and_then(|c| c.as_i64()).unwrap_or(-1) != 0 {
-            return Err(Error::Api(
-                rsp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+            return Err(Error::from_response(&rsp, "API error"));
         }
 
-        Ok(rsp.get("type").and_then(|v| v.as_i64()) == Some(-1))
+        let previous = rsp.get("type").and_then(|v| v.as_i64()).unwrap_or(-1);
+        Ok(VoteStatus::after_vote(like, previous))
     }
 
     /// 感谢文章
     ///
     /// - `id` 文章id
     ///
-    /// 返回执行结果
-    pub async fn thank(&self, id: &str) -> Result<ResponseResult, Error> {
+    /// 返回感谢后的感谢数 [ThankResult]，已感谢过时返回 [`Error::AlreadyThanked`]
+    pub async fn thank(&self, id: &str) -> Result<ThankResult, Error> {
         let url = build_http_path(
             "article/thank",
             &[
                 ("articleId", id.to_string()),
-                ("apiKey", self.api_key.clone()),
+                ("apiKey", self.session.api_key()),
             ],
         );
 
         let rsp = post(&url, None).await?;
 
-        ResponseResult::from_value(&rsp)
+        ThankResult::from_value(&rsp)
     }
 
     /// 收藏/取消收藏文章
@@ -346,12 +573,9 @@ impl Article {
     pub async fn follow(&self, id: &str) -> Result<ResponseResult, Error> {
         let url = "follow/article".to_string();
 
-        let data = json!({
-            "apiKey": self.api_key,
-            "followingId": id,
-        });
+        let data = FollowBody { following_id: id };
 
-        let rsp = post(&url, Some(data)).await?;
+        let rsp = post_with_key(&url, &data, &self.session.api_key()).await?;
 
         ResponseResult::from_value(&rsp)
     }
@@ -364,12 +588,9 @@ impl Article {
     pub async fn watch(&self, following_id: &str) -> Result<ResponseResult, Error> {
         let url = "follow/article-watch".to_string();
 
-        let data = json!({
-            "apiKey": self.api_key,
-            "followingId": following_id,
-        });
+        let data = FollowBody { following_id };
 
-        let rsp = post(&url, Some(data)).await?;
+        let rsp = post_with_key(&url, &data, &self.session.api_key()).await?;
 
         ResponseResult::from_value(&rsp)
     }
@@ -382,11 +603,7 @@ impl Article {
     pub async fn reward(&self, id: &str) -> Result<ResponseResult, Error> {
         let url = build_http_path("article/reward", &[("articleId", id.to_string())]);
 
-        let data = json!({
-            "apiKey": self.api_key,
-        });
-
-        let rsp = post(&url, Some(data)).await?;
+        let rsp = post_with_key(&url, &EmptyBody {}, &self.session.api_key()).await?;
 
         ResponseResult::from_value(&rsp)
     }
@@ -399,27 +616,51 @@ impl Article {
     pub async fn heat(&self, id: &str) -> Result<u32, Error> {
         let url = build_http_path(
             &format!("api/article/heat/{}", id),
-            &[("apiKey", self.api_key.clone())],
+            &[("apiKey", self.session.api_key())],
         );
 
         let rsp = get(&url).await?;
 
         if rsp.get("code").and_then(|c| c.as_i64()).unwrap_or(-1) != 0 {
-            return Err(Error::Api(
-                rsp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+            return Err(Error::from_response(&rsp, "API error"));
         }
 
         let heat = rsp["articleHeat"]
             .as_u64()
-            .ok_or_else(|| Error::Api("Missing heat data in response".to_string()))?
+            .ok_or_else(|| Error::api("Missing heat data in response".to_string()))?
             as u32;
 
         Ok(heat)
     }
 
+    /// 获取热门文章及其当前在线人数
+    ///
+    /// 在线人数并发查询，同时最多 [`TRENDING_CONCURRENCY`] 个请求；
+    /// 单篇文章查询失败时在线人数记为 0。
+    ///
+    /// - `size` 文章数量
+    ///
+    /// 返回 (文章, 在线人数) 列表，顺序与热门列表一致
+    pub async fn trending(&self, size: u32) -> Result<Vec<(ArticleDetail, u32)>, Error> {
+        let articles = self.list(ArticleListType::Hot, 1, size, None).await?.list;
+
+        let trending = stream::iter(articles)
+            .map(|article| async move {
+                let heat = self.heat(&article.oId).await.unwrap_or(0);
+                (article, heat)
+            })
+            .buffered(TRENDING_CONCURRENCY)
+            .collect()
+            .await;
+
+        Ok(trending)
+    }
+
     /// 添加文章监听器
     ///
+    /// 返回的连接由调用方持有，`Article` 不会保存；不再需要时必须调用
+    /// [`WebSocketClient::disconnect`]，仅丢弃不会关闭连接。需要由 `Article` 管理连接时请使用 [`Article::listen`]。
+    ///
     /// - `id` 文章id
     /// - `type_` 文章类型
     /// - `callback` 监听回调
@@ -432,21 +673,110 @@ impl Article {
         callback: ArticleListener,
     ) -> Result<WebSocketClient, Error> {
         let url = build_ws_url(
-            "fishpi.cn",
+            &ws_domain(),
             "article-channel",
             &[
-                ("apiKey", self.api_key.clone()),
+                ("apiKey", self.session.api_key()),
                 ("articleId", id.to_string()),
                 ("articleType", (type_ as u8).to_string()),
             ],
         )
-        .map_err(|e| Error::Api(format!("WebSocket URL build failed: {}", e)))?;
+        .map_err(|e| Error::api(format!("WebSocket URL build failed: {}", e)))?;
 
         let handler = ArticleMessageHandler::new(callback);
         let ws = WebSocketClient::connect(&url, handler)
             .await
-            .map_err(|e| Error::Api(format!("WebSocket connection failed: {}", e)))?;
+            .map_err(|e| Error::api(format!("WebSocket connection failed: {}", e)))?;
 
         Ok(ws)
     }
+
+    /// 监听文章频道，连接由 `Article` 保存，通过 [`Article::off`] 或 [`Article::disconnect`] 关闭
+    ///
+    /// 同一篇文章重复监听时，会先关闭之前的连接。
+    ///
+    /// - `id` 文章id
+    /// - `type_` 文章类型
+    /// - `callback` 监听回调
+    pub async fn listen(
+        &self,
+        id: &str,
+        type_: ArticleType,
+        callback: ArticleListener,
+    ) -> Result<(), Error> {
+        let ws = self.add_listener(id, type_, callback).await?;
+        let previous = self
+            .listeners
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(id.to_string(), ws);
+        if let Some(previous) = previous {
+            previous.disconnect();
+        }
+        Ok(())
+    }
+
+    /// 停止监听指定文章，关闭对应连接
+    ///
+    /// - `id` 文章id
+    ///
+    /// 返回是否存在该文章的连接
+    pub fn off(&self, id: &str) -> bool {
+        let ws = self
+            .listeners
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(id);
+        ws.map(|ws| ws.disconnect()).is_some()
+    }
+
+    /// 正在监听的文章 id
+    pub fn listening(&self) -> Vec<String> {
+        self.listeners
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    /// 关闭所有通过 [`Article::listen`] 建立的连接
+    pub fn disconnect(&self) {
+        let listeners: Vec<_> = self
+            .listeners
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .drain()
+            .map(|(_, ws)| ws)
+            .collect();
+        for ws in listeners {
+            ws.disconnect();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ArticleEvent;
+    use serde_json::json;
+
+    #[test]
+    fn parse_article_events() {
+        let comment = ArticleEvent::from_value(json!({
+            "type": "comment",
+            "oId": "1",
+            "commentContent": "<p>hi</p>",
+            "commentAuthorName": "someone"
+        }));
+        match comment {
+            ArticleEvent::CommentAdded(c) => assert_eq!(c.author, "someone"),
+            other => panic!("unexpected event: {:?}", other),
+        }
+
+        let heat = ArticleEvent::from_value(json!({ "type": "articleHeat", "articleHeat": 3 }));
+        assert!(matches!(heat, ArticleEvent::HeatChanged(3)));
+
+        let raw = ArticleEvent::from_value(json!({ "type": "somethingElse" }));
+        assert!(matches!(raw, ArticleEvent::Raw(_)));
+    }
 }
@@ -10,7 +10,9 @@
 //! # 方法列表
 //!
 //! - [`BreezeMoon::new`] - 创建新的清风明月客户端实例。
+//! - [`BreezeMoon::with_session`] - 使用共享的登录会话创建实例。
 //! - [`BreezeMoon::list`] - 获取清风明月列表。
+//! - [`BreezeMoon::list_stream`] - 以异步流的形式逐页获取清风明月列表。
 //! - [`BreezeMoon::send`] - 发送清风明月。
 //!
 //! # 示例
@@ -35,20 +37,33 @@
 //!     Ok(())
 //! }
 //! ```
+use futures_util::{Stream, StreamExt, stream};
 use serde_json::json;
+use std::sync::Arc;
 
 use crate::{
+    api::session::Session,
     model::breezemoon::BreezemoonContent,
-    utils::{ResponseResult, build_http_path, error::Error, get, post},
+    utils::{ResponseResult, as_array_or_empty, build_http_path, error::Error, get, post},
 };
 
 pub struct BreezeMoon {
-    api_key: String,
+    session: Arc<Session>,
 }
 
 impl BreezeMoon {
     pub fn new(api_key: String) -> Self {
-        Self { api_key }
+        Self::with_session(Session::new(api_key))
+    }
+
+    /// 使用共享的登录会话创建实例，API key 从会话读取
+    pub fn with_session(session: Arc<Session>) -> Self {
+        Self { session }
+    }
+
+    /// 重新设置 API key，共享同一会话的模块一并生效
+    pub fn set_api_key(&mut self, api_key: String) {
+        self.session.set_api_key(api_key);
     }
 
     /// 获取清风明月列表
@@ -74,22 +89,18 @@ impl BreezeMoon {
             &[
                 ("p", page.to_string()),
                 ("size", size.to_string()),
-                ("apiKey", self.api_key.clone()),
+                ("apiKey", self.session.api_key()),
             ],
         );
 
         let rsp = get(&url).await?;
 
         if rsp.get("code").and_then(|c| c.as_i64()).unwrap_or(-1) != 0 {
-            return Err(Error::Api(
-                rsp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+            return Err(Error::from_response(&rsp, "API error"));
         }
 
         let data = rsp.get("data").unwrap_or(&rsp);
-        let breezemoons = data["breezemoons"]
-            .as_array()
-            .ok_or_else(|| Error::Api("breezemoons is not an array".to_string()))?
+        let breezemoons = as_array_or_empty(&data["breezemoons"])?
             .iter()
             .map(BreezemoonContent::from_value)
             .collect::<Result<Vec<_>, _>>()?;
@@ -97,6 +108,38 @@ impl BreezeMoon {
         Ok(breezemoons)
     }
 
+    /// 以异步流的形式逐页获取清风明月列表
+    ///
+    /// 接口不返回总页数，某页数量不足 `size` 时视为最后一页。请求失败时产生一个错误并结束。
+    ///
+    /// - `size` 每页数量
+    /// - `user` 指定用户（可选）
+    ///
+    /// 返回清风明月流
+    pub fn list_stream(
+        &self,
+        size: u32,
+        user: Option<String>,
+    ) -> impl Stream<Item = Result<BreezemoonContent, Error>> + use<> {
+        let session = Arc::clone(&self.session);
+        stream::unfold(Some(1), move |page| {
+            let breezemoon = BreezeMoon::with_session(Arc::clone(&session));
+            let user = user.clone();
+            async move {
+                let page = page?;
+                match breezemoon.list(page, size, user.as_deref()).await {
+                    Ok(list) => {
+                        let next_page =
+                            (size > 0 && list.len() >= size as usize).then_some(page + 1);
+                        Some((list.into_iter().map(Ok).collect(), next_page))
+                    }
+                    Err(e) => Some((vec![Err(e)], None)),
+                }
+            }
+        })
+        .flat_map(stream::iter)
+    }
+
     /// 发送清风明月
     ///
     /// - `content` 内容
@@ -106,7 +149,7 @@ impl BreezeMoon {
         let url = "breezemoon".to_string();
 
         let data_json = json!({
-            "apiKey": self.api_key,
+            "apiKey": self.session.api_key(),
             "breezemoonContent": content,
         });
 
@@ -15,17 +15,30 @@
 //! # 方法列表
 //!
 //! - [`Chat::new`] - 创建新的私聊客户端实例。
+//! - [`Chat::with_session`] - 使用共享的登录会话创建实例。
 //! - [`Chat::connect`] - 连接私聊 WebSocket。
 //! - [`Chat::reconnect`] - 重连私聊 WebSocket。
+//! - [`Chat::target`] - 获取当前私聊连接的对方用户。
+//! - [`Chat::is_connected`] - 是否已连接。
+//! - [`Chat::on_reconnecting`] - 监听重连状态，每次重连前调用。
+//! - [`Chat::set_api_key`] - 重新设置 API key。
+//! - [`Chat::set_session`] - 设置共享的登录会话。
+//! - [`Chat::is_own_message`] - 判断私聊消息是否为当前用户发送。
 //! - [`Chat::on_notice`] - 监听通知消息事件。
 //! - [`Chat::on_data`] - 监听普通消息事件。
 //! - [`Chat::on_revoke`] - 监听消息撤回事件。
 //! - [`Chat::off`] - 移除事件监听器。
 //! - [`Chat::disconnect`] - 断开连接。
+//! - [`Chat::send`] - 发送私聊消息。
+//! - [`Chat::send_ws`] - 通过已连接的会话发送私聊消息。
 //! - [`Chat::list`] - 获取有私聊用户列表第一条消息。
 //! - [`Chat::history`] - 获取用户私聊历史消息。
+//! - [`Chat::history_page`] - 分页获取用户私聊历史消息，并返回是否还有下一页。
+//! - [`Chat::history_all`] - 以异步流的形式逐页获取与用户的全部私聊历史消息。
+//! - [`Chat::history_before`] - 以消息 Id 为游标获取更早的私聊历史消息。
 //! - [`Chat::mark_as_read`] - 标记用户消息已读。
 //! - [`Chat::unread`] - 获取未读消息。
+//! - [`Chat::unread_count`] - 获取未读消息数量。
 //! - [`Chat::revoke`] - 撤回私聊消息。
 //!
 //! # 示例
@@ -75,16 +88,22 @@
 //! - `Revoke` - 消息撤回。
 
 use crate::{
+    api::session::Session,
     api::ws::{
         ParsedMessageHandler, RetryPolicy, WebSocketError, WsConnection, WsLogHook, build_ws_url,
     },
-    model::chat::{ChatData, ChatMsgType, ChatNotice, ChatRevoke},
-    utils::{build_http_path, error::Error, get},
+    model::chat::{ChatData, ChatHistoryPage, ChatMsgType, ChatNotice, ChatRevoke},
+    utils::{as_array_or_empty, build_http_path, error::Error, get, ws_domain},
 };
+use futures_util::{Stream, StreamExt, stream};
 use serde_json::Value;
-use std::{str::FromStr, sync::Arc};
+use std::{collections::HashMap, str::FromStr, sync::Arc, time::Duration};
 
-const DOMAIN: &str = "fishpi.cn";
+/// [`Chat::history_before`] 最多翻阅的页数
+pub const HISTORY_BEFORE_MAX_PAGES: u32 = 10;
+
+/// [`Chat::history_before`] 单次最多返回的消息数
+pub const HISTORY_BEFORE_MAX_SIZE: u32 = 100;
 
 #[derive(Clone, Debug)]
 pub enum ChatEventData {
@@ -165,20 +184,28 @@ fn detect_chat_msg_type(json: &Value) -> Result<ChatMsgType, Error> {
 pub struct Chat {
     connection: WsConnection,
     handler: ChatHandler,
-    api_key: String,
+    /// 当前私聊连接的对方用户，`None` 表示连接的是用户通知频道
+    target: Option<String>,
+    session: Arc<Session>,
 }
 
 impl Chat {
     pub fn new(api_key: String) -> Self {
+        Self::with_session(Session::new(api_key))
+    }
+
+    /// 使用共享的登录会话创建实例，API key 从会话读取
+    pub fn with_session(session: Arc<Session>) -> Self {
         Self {
             connection: WsConnection::new(),
             handler: ChatHandler::new(parse_chat_message, None, "chat"),
-            api_key,
+            target: None,
+            session,
         }
     }
 
     fn ws_url(&self, user: Option<&str>) -> Result<String, WebSocketError> {
-        let mut params = vec![("apiKey", self.api_key.clone())];
+        let mut params = vec![("apiKey", self.session.api_key())];
         let path = if let Some(user) = user {
             params.push(("toUser", user.to_string()));
             "chat-channel"
@@ -186,7 +213,7 @@ impl Chat {
             "user-channel"
         };
 
-        build_ws_url(DOMAIN, path, &params)
+        build_ws_url(&ws_domain(), path, &params)
     }
 
     pub async fn connect(
@@ -195,6 +222,7 @@ impl Chat {
         user: Option<String>,
     ) -> Result<(), WebSocketError> {
         let url = self.ws_url(user.as_deref())?;
+        self.target = user;
 
         self.connection
             .connect(reload, &url, self.handler.clone())
@@ -204,14 +232,49 @@ impl Chat {
     /// 重连
     pub async fn reconnect(&mut self, user: Option<String>) -> Result<(), WebSocketError> {
         let url = self.ws_url(user.as_deref())?;
+        self.target = user;
 
         self.connection.reconnect(&url, self.handler.clone()).await
     }
 
+    /// 当前私聊连接的对方用户
+    pub fn target(&self) -> Option<&str> {
+        self.target.as_deref()
+    }
+
+    /// 是否已连接
+    pub fn is_connected(&self) -> bool {
+        self.connection.is_connected()
+    }
+
+    /// 重新设置 API key，保留已注册的监听器，共享同一会话的模块一并生效
+    ///
+    /// 已建立的连接仍使用旧 key，需调用 [`Chat::reconnect`] 生效。
+    pub fn set_api_key(&mut self, api_key: String) {
+        self.session.set_api_key(api_key);
+    }
+
+    /// 设置共享的登录会话，之后从该会话读取 API key
+    pub fn set_session(&mut self, session: Arc<Session>) {
+        self.session = session;
+    }
+
+    /// 判断私聊消息是否为当前用户发送
+    ///
+    /// - `msg` 私聊消息
+    pub async fn is_own_message(&self, msg: &ChatData) -> Result<bool, Error> {
+        self.session.is_self(&msg.senderUserName).await
+    }
+
     pub fn set_reconnect_policy(&mut self, policy: RetryPolicy) {
         self.connection.set_retry_policy(policy);
     }
 
+    /// 设置心跳间隔，默认 60 秒，`None` 表示不发送心跳，下次连接时生效
+    pub fn set_heartbeat_interval(&mut self, heartbeat_interval: Option<Duration>) {
+        self.connection.set_heartbeat_interval(heartbeat_interval);
+    }
+
     pub fn on_ws_log<F>(&mut self, hook: F)
     where
         F: Fn(&str) + Send + Sync + 'static,
@@ -221,6 +284,16 @@ impl Chat {
         self.handler.set_log_hook_arc(hook);
     }
 
+    /// 监听重连状态，每次重连前调用，参数为 (第几次尝试, 重连前的等待时间)
+    ///
+    /// 可用于在界面上显示“重连中”，与连接彻底失败区分开。下次连接时生效。
+    pub fn on_reconnecting<F>(&mut self, hook: F)
+    where
+        F: Fn(u32, Duration) + Send + Sync + 'static,
+    {
+        self.connection.set_reconnect_hook(hook);
+    }
+
     /// 监听通知消息事件
     pub async fn on_notice<F>(&self, listener: F)
     where
@@ -289,23 +362,42 @@ impl Chat {
     pub fn send_ws(&self, content: &str) -> Result<(), Error> {
         self.connection
             .send_text(content)
-            .map_err(|e| Error::Api(format!("WS send failed: {}", e)))
+            .map_err(|e| Error::api(format!("WS send failed: {}", e)))
+    }
+
+    /// 发送私聊消息
+    ///
+    /// 服务端没有发送私聊消息的 HTTP 接口，消息只能通过目标用户的 chat-channel 发送，
+    /// 因此需要先调用 `connect(..., Some(to_user))` 建立与 `to_user` 的会话连接。
+    /// 发送成功后新消息会通过 [`Chat::on_data`] 推送，其中包含消息 Id。
+    ///
+    /// - `to_user` 接收者用户名
+    /// - `content` 消息内容，支持 Markdown
+    pub fn send(&self, to_user: &str, content: &str) -> Result<(), Error> {
+        if self.target() != Some(to_user) || !self.is_connected() {
+            return Err(Error::api(format!(
+                "Not connected to the chat channel of {}, call connect with Some(\"{}\") first",
+                to_user, to_user
+            )));
+        }
+        self.send_ws(content)
     }
 
     /// 获取有私聊用户列表第一条消息
     ///
+    /// 每个会话的 `unreadCount` 由未读消息按发送者统计得出；
+    /// 查询未读消息失败时不影响列表本身，`unreadCount` 保持为 0。
+    ///
     /// 返回 私聊消息列表
     pub async fn list(&self) -> Result<Vec<ChatData>, Error> {
-        let url = build_http_path("chat/get-list", &[("apiKey", self.api_key.clone())]);
+        let url = build_http_path("chat/get-list", &[("apiKey", self.session.api_key())]);
 
         let resp = get(&url).await?;
 
         if let Some(code) = resp.get("code").and_then(|c| c.as_i64())
             && code != 0
         {
-            return Err(Error::Api(
-                resp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+            return Err(Error::from_response(&resp, "API error"));
         }
 
         let mut chat_list = Vec::new();
@@ -316,6 +408,10 @@ impl Chat {
             }
         }
 
+        if let Ok(unread) = self.unread().await {
+            fill_unread_counts(&mut chat_list, &unread);
+        }
+
         Ok(chat_list)
     }
 
@@ -333,34 +429,115 @@ impl Chat {
         size: u32,
         autoread: bool,
     ) -> Result<Vec<ChatData>, Error> {
-        let url = build_http_path(
-            "chat/get-message",
-            &[
-                ("apiKey", self.api_key.clone()),
-                ("page", page.to_string()),
-                ("pageSize", size.to_string()),
-                ("toUser", user.clone()),
-            ],
-        );
-        let resp = get(&url).await?;
-        if let Some(code) = resp.get("result").and_then(|c| c.as_i64())
-            && code != 0
-        {
-            return Err(Error::Api(
-                resp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+        Ok(self
+            .history_page(user, page, size, autoread)
+            .await?
+            .messages)
+    }
+
+    /// 分页获取用户私聊历史消息
+    ///
+    /// * `user` 对方用户名
+    /// * `page` 页数
+    /// * `size` 每页消息数量
+    /// * `autoread` 是否自动标记为已读
+    ///
+    /// 返回 [ChatHistoryPage]，本页消息数量不足 `size` 时 `has_more` 为 `false`
+    pub async fn history_page(
+        &self,
+        user: String,
+        page: u32,
+        size: u32,
+        autoread: bool,
+    ) -> Result<ChatHistoryPage, Error> {
+        let messages = fetch_history(&self.session.api_key(), &user, page, size).await?;
+        if autoread {
+            self.mark_as_read(user).await?;
         }
-        let mut chat_list = Vec::new();
-        if let Some(list) = resp["data"].as_array() {
-            for item in list {
-                let chat_data = ChatData::from_value(item)?;
-                chat_list.push(chat_data);
+        Ok(ChatHistoryPage::new(messages, page, size))
+    }
+
+    /// 逐页获取与用户的全部私聊历史消息
+    ///
+    /// 从第一页开始按需翻页，直到某页消息数量不足 `size`。请求失败时产生一个错误并结束。
+    /// 不会自动标记已读。
+    ///
+    /// * `user` 对方用户名
+    /// * `size` 每页消息数量
+    ///
+    /// 返回私聊消息流
+    pub fn history_all(
+        &self,
+        user: String,
+        size: u32,
+    ) -> impl Stream<Item = Result<ChatData, Error>> + use<> {
+        let api_key = self.session.api_key();
+        stream::unfold(Some(1), move |page| {
+            let api_key = api_key.clone();
+            let user = user.clone();
+            async move {
+                let page = page?;
+                match fetch_history(&api_key, &user, page, size).await {
+                    Ok(messages) => {
+                        let next = ChatHistoryPage::new(messages, page, size);
+                        let next_page = next.has_more.then_some(page + 1);
+                        Some((next.messages.into_iter().map(Ok).collect(), next_page))
+                    }
+                    Err(e) => Some((vec![Err(e)], None)),
+                }
+            }
+        })
+        .flat_map(stream::iter)
+    }
+
+    /// 以消息 Id 为游标获取更早的私聊历史消息
+    ///
+    /// 服务端只提供页码分页，这里从第一页开始翻页，只保留 Id 严格小于游标的消息，
+    /// 避免翻页期间有新消息导致的重复或遗漏。不会自动标记已读。
+    /// 最多翻阅 [`HISTORY_BEFORE_MAX_PAGES`] 页，翻到上限时返回已找到的消息，可能不足 `size` 条。
+    ///
+    /// * `user` 对方用户名
+    /// * `oid` 游标消息 Id
+    /// * `size` 返回消息数量，最大 [`HISTORY_BEFORE_MAX_SIZE`]
+    ///
+    /// 返回 私聊消息列表，空列表表示已到最早的消息；游标过旧、翻到上限仍没有更早的消息时返回 [Error::Api]
+    pub async fn history_before(
+        &self,
+        user: String,
+        oid: &str,
+        size: u32,
+    ) -> Result<Vec<ChatData>, Error> {
+        let before = oid
+            .parse::<u64>()
+            .map_err(|e| Error::Parse(format!("Invalid message id {}: {}", oid, e)))?;
+
+        let size = size.min(HISTORY_BEFORE_MAX_SIZE);
+        let mut messages = Vec::new();
+        let mut reached_end = false;
+        for page in 1..=HISTORY_BEFORE_MAX_PAGES {
+            if messages.len() >= size as usize {
+                break;
+            }
+            let list = self.history(user.clone(), page, size, false).await?;
+            let fetched = list.len();
+            messages.extend(
+                list.into_iter()
+                    .filter(|m| m.oId.parse::<u64>().is_ok_and(|id| id < before)),
+            );
+            if fetched < size as usize {
+                reached_end = true;
+                break;
             }
         }
-        if autoread {
-            self.mark_as_read(user).await?;
+
+        if messages.is_empty() && size > 0 && !reached_end {
+            return Err(Error::api(format!(
+                "消息 {} 之前的内容超出翻页上限 {} 页",
+                oid, HISTORY_BEFORE_MAX_PAGES
+            )));
         }
-        Ok(chat_list)
+        messages.truncate(size as usize);
+        Ok(messages)
     }
 
     /// 标记用户消息已读
@@ -371,7 +548,7 @@ impl Chat {
     pub async fn mark_as_read(&self, user: String) -> Result<bool, Error> {
         let to_user_url = build_http_path(
             "chat/mark-as-read",
-            &[("toUser", user.clone()), ("apiKey", self.api_key.clone())],
+            &[("toUser", user.clone()), ("apiKey", self.session.api_key())],
         );
         let first = get(&to_user_url).await;
         match first {
@@ -384,21 +561,19 @@ impl Chat {
                     let need_from_user_retry =
                         msg.contains("fromUserJSON") || msg.contains("Cannot invoke");
                     if !need_from_user_retry {
-                        return Err(Error::Api(msg));
+                        return Err(result_error(&resp));
                     }
 
                     // Some backend nodes require fromUser for mark-as-read.
                     let from_user_url = build_http_path(
                         "chat/mark-as-read",
-                        &[("fromUser", user), ("apiKey", self.api_key.clone())],
+                        &[("fromUser", user), ("apiKey", self.session.api_key())],
                     );
                     let resp = get(&from_user_url).await?;
                     if let Some(code) = resp.get("result").and_then(|c| c.as_i64())
                         && code != 0
                     {
-                        return Err(Error::Api(
-                            resp["msg"].as_str().unwrap_or("API error").to_string(),
-                        ));
+                        return Err(result_error(&resp));
                     }
                     return Ok(true);
                 }
@@ -413,15 +588,13 @@ impl Chat {
                 // Some backend nodes require fromUser for mark-as-read.
                 let from_user_url = build_http_path(
                     "chat/mark-as-read",
-                    &[("fromUser", user), ("apiKey", self.api_key.clone())],
+                    &[("fromUser", user), ("apiKey", self.session.api_key())],
                 );
                 let resp = get(&from_user_url).await?;
                 if let Some(code) = resp.get("result").and_then(|c| c.as_i64())
                     && code != 0
                 {
-                    return Err(Error::Api(
-                        resp["msg"].as_str().unwrap_or("API error").to_string(),
-                    ));
+                    return Err(result_error(&resp));
                 }
                 Ok(true)
             }
@@ -432,17 +605,15 @@ impl Chat {
     ///
     /// 返回 未读消息列表
     pub async fn unread(&self) -> Result<Vec<ChatData>, Error> {
-        let url = build_http_path("chat/has-unread", &[("apiKey", self.api_key.clone())]);
+        let url = build_http_path("chat/has-unread", &[("apiKey", self.session.api_key())]);
         let resp = get(&url).await?;
 
-        let unread_len = resp["result"].as_i64().unwrap_or(0);
+        let unread_len = unread_result(&resp)?;
         if unread_len == 0 {
             return Ok(Vec::new());
         }
 
-        let chat_list = resp["data"]
-            .as_array()
-            .ok_or_else(|| Error::Api("Data is not an array".to_string()))?
+        let chat_list = as_array_or_empty(&resp["data"])?
             .iter()
             .map(ChatData::from_value)
             .collect::<Result<Vec<_>, _>>()?;
@@ -450,6 +621,16 @@ impl Chat {
         Ok(chat_list)
     }
 
+    /// 获取未读消息数量，不解析消息内容，适合轮询角标
+    ///
+    /// 返回 未读消息数量；接口失败时返回错误，API key 失效时返回 [Error::Unauthorized]
+    pub async fn unread_count(&self) -> Result<u64, Error> {
+        let url = build_http_path("chat/has-unread", &[("apiKey", self.session.api_key())]);
+        let resp = get(&url).await?;
+
+        unread_result(&resp)
+    }
+
     /// 撤回私聊消息
     ///
     /// - `msgId` 消息 ID
@@ -459,7 +640,7 @@ impl Chat {
         let url = build_http_path(
             "chat/revoke",
             &[
-                ("apiKey", self.api_key.clone()),
+                ("apiKey", self.session.api_key()),
                 ("oId", msg_id.to_string()),
             ],
         );
@@ -468,20 +649,121 @@ impl Chat {
         if let Some(code) = resp.get("result").and_then(|c| c.as_i64())
             && code != 0
         {
-            return Err(Error::Api(
-                resp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+            return Err(result_error(&resp));
         }
 
         Ok(true)
     }
 }
 
+/// 将私聊接口的失败响应转换为错误，私聊接口的错误码在 `result` 字段
+fn result_error(resp: &Value) -> Error {
+    Error::from_code(
+        resp.get("result").and_then(Value::as_i64),
+        resp["msg"].as_str().unwrap_or("API error"),
+    )
+}
+
+/// 读取未读接口返回的未读数，`result` 为负数或 `code` 非 0 时返回错误
+fn unread_result(resp: &Value) -> Result<u64, Error> {
+    if let Some(code) = resp.get("code").and_then(Value::as_i64)
+        && code != 0
+    {
+        return Err(Error::from_response(resp, "API error"));
+    }
+    match resp.get("result").and_then(Value::as_i64) {
+        Some(count) if count >= 0 => Ok(count as u64),
+        Some(_) => Err(result_error(resp)),
+        None => Err(Error::Parse(
+            "Missing result in unread response".to_string(),
+        )),
+    }
+}
+
+/// 按未读消息的发送者统计每个会话的未读数
+fn fill_unread_counts(conversations: &mut [ChatData], unread: &[ChatData]) {
+    let mut counts: HashMap<&str, u64> = HashMap::new();
+    for msg in unread {
+        *counts.entry(msg.senderUserName.as_str()).or_default() += 1;
+    }
+
+    for conv in conversations {
+        conv.unreadCount = counts
+            .get(conv.senderUserName.as_str())
+            .or_else(|| counts.get(conv.receiverUserName.as_str()))
+            .copied()
+            .unwrap_or(0);
+    }
+}
+
+/// 请求一页私聊历史消息
+async fn fetch_history(
+    api_key: &str,
+    user: &str,
+    page: u32,
+    size: u32,
+) -> Result<Vec<ChatData>, Error> {
+    let url = build_http_path(
+        "chat/get-message",
+        &[
+            ("apiKey", api_key.to_string()),
+            ("page", page.to_string()),
+            ("pageSize", size.to_string()),
+            ("toUser", user.to_string()),
+        ],
+    );
+    let resp = get(&url).await?;
+    if let Some(code) = resp.get("result").and_then(|c| c.as_i64())
+        && code != 0
+    {
+        return Err(result_error(&resp));
+    }
+    let mut chat_list = Vec::new();
+    if let Some(list) = resp["data"].as_array() {
+        for item in list {
+            let chat_data = ChatData::from_value(item)?;
+            chat_list.push(chat_data);
+        }
+    }
+    Ok(chat_list)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{ChatEventData, ChatEventType, parse_chat_message};
+    use super::{
+        ChatEventData, ChatEventType, fill_unread_counts, parse_chat_message, result_error,
+        unread_result,
+    };
+    use crate::model::chat::{ChatData, ChatHistoryPage};
+    use crate::utils::error::Error;
     use serde_json::json;
 
+    #[test]
+    fn history_page_has_more_only_when_full() {
+        let msg = || ChatData::from_value(&json!({"oId": "1"})).unwrap();
+        assert!(ChatHistoryPage::new(vec![msg(), msg()], 1, 2).has_more);
+        assert!(!ChatHistoryPage::new(vec![msg()], 2, 2).has_more);
+        assert!(!ChatHistoryPage::new(Vec::new(), 1, 0).has_more);
+    }
+
+    #[test]
+    fn fill_unread_counts_groups_by_peer() {
+        let mut conversations = vec![
+            ChatData::from_value(&json!({"senderUserName": "me", "receiverUserName": "bob"}))
+                .unwrap(),
+            ChatData::from_value(&json!({"senderUserName": "carol", "receiverUserName": "me"}))
+                .unwrap(),
+        ];
+        let unread = vec![
+            ChatData::from_value(&json!({"senderUserName": "bob"})).unwrap(),
+            ChatData::from_value(&json!({"senderUserName": "bob"})).unwrap(),
+        ];
+
+        fill_unread_counts(&mut conversations, &unread);
+        assert_eq!(conversations[0].unreadCount, 2);
+        assert_eq!(conversations[1].unreadCount, 0);
+    }
+
     #[test]
     fn parse_chat_notice_message() {
         let payload = json!({
@@ -533,4 +815,21 @@ mod tests {
             _ => panic!("unexpected event variant"),
         }
     }
+
+    #[test]
+    fn result_error_reads_result_code() {
+        let err = result_error(&json!({ "result": -1, "msg": "发送失败" }));
+        assert!(matches!(err, Error::Api { code: Some(-1), .. }));
+        let err = result_error(&json!({ "result": -1, "msg": "请先登录" }));
+        assert!(matches!(err, Error::Unauthorized { code: Some(-1), .. }));
+    }
+
+    #[test]
+    fn unread_result_surfaces_failures() {
+        assert_eq!(unread_result(&json!({ "result": 3 })).unwrap(), 3);
+        let err = unread_result(&json!({ "result": -1, "msg": "请先登录" })).unwrap_err();
+        assert!(matches!(err, Error::Unauthorized { .. }));
+        let err = unread_result(&json!({ "code": 401, "msg": "401" })).unwrap_err();
+        assert!(err.is_auth());
+    }
 }
@@ -15,35 +15,56 @@
 //! # 方法列表
 //!
 //! - [`ChatRoom::new`] - 创建新的聊天室客户端实例。
+//! - [`ChatRoom::with_session`] - 使用共享的登录会话创建实例。
 //! - [`ChatRoom::get_node`] - 获取聊天室节点信息。
 //! - [`ChatRoom::get_ws_url`] - 获取 WebSocket URL。
 //! - [`ChatRoom::connect`] - 连接聊天室。
 //! - [`ChatRoom::reconnect`] - 重连聊天室。
+//! - [`ChatRoom::is_connected`] - 是否已连接。
+//! - [`ChatRoom::on_reconnecting`] - 监听重连状态，每次重连前调用。
 //! - [`ChatRoom::on_online`] - 监听在线用户更新事件。
+//! - [`ChatRoom::on_online_changed`] - 监听在线用户进出事件。
 //! - [`ChatRoom::on_discuss`] - 监听话题变更事件。
 //! - [`ChatRoom::on_revoke`] - 监听消息撤回事件。
 //! - [`ChatRoom::on_msg`] - 监听普通消息事件。
+//! - [`ChatRoom::on_edit`] - 监听消息编辑事件。
 //! - [`ChatRoom::on_barrager`] - 监听弹幕消息事件。
 //! - [`ChatRoom::on_redpacket`] - 监听红包消息事件。
 //! - [`ChatRoom::on_redpacketstatus`] - 监听红包状态事件。
 //! - [`ChatRoom::on_music`] - 监听音乐消息事件。
 //! - [`ChatRoom::on_weather`] - 监听天气消息事件。
 //! - [`ChatRoom::on_custom`] - 监听进出场消息事件。
+//! - [`ChatRoom::on_unknown`] - 监听 SDK 尚未支持的消息类型。
+//! - [`ChatRoom::supported_message_types`] - 获取 SDK 已支持的消息类型。
+//! - [`ChatRoom::on_all`] - 监听所有事件。
+//! - [`ChatRoom::on_all_typed`] - 监听所有事件，同时获得事件类型名称。
 //! - [`ChatRoom::off`] - 移除事件监听器。
 //! - [`ChatRoom::disconnect`] - 断开连接。
-//! - [`ChatRoom::send`] - 发送消息。
+//! - [`ChatRoom::send`] - 发送消息，返回新消息的 oId。
+//! - [`ChatRoom::send_with`] - 按指定选项发送消息，可选纯文本及客户端标识。
+//! - [`ChatRoom::send_ws`] - 通过 WebSocket 发送消息。
+//! - [`ChatRoom::send_raw`] - 通过 WebSocket 发送原始 JSON，不做校验。
 //! - [`ChatRoom::get_discuss`] - 获取当前话题。
 //! - [`ChatRoom::set_discuss`] - 设置当前话题。
 //! - [`ChatRoom::get_online_count`] - 获取在线人数。
+//! - [`ChatRoom::get_onlines`] - 获取在线用户列表。
 //! - [`ChatRoom::set_api_key`] - 设置 API 密钥。
+//! - [`ChatRoom::set_session`] - 设置共享的登录会话。
+//! - [`ChatRoom::is_own_message`] - 判断消息是否为当前用户发送。
+//! - [`ChatRoom::is_mentioned`] - 判断消息是否提及当前用户。
 //! - [`ChatRoom::set_client_type`] - 设置客户端类型。
 //! - [`ChatRoom::history`] - 查询历史消息。
 //! - [`ChatRoom::get_msg_around`] - 获取指定消息附近的聊天室消息。
+//! - [`ChatRoom::history_since`] - 获取指定消息之后的消息。
+//! - [`ChatRoom::last_oid`] - 获取最近收到的消息 oId，断线重连后据此补发消息。
 //! - [`ChatRoom::revoke`] - 撤回消息。
-//! - [`ChatRoom::barrager`] - 发送弹幕。
+//! - [`ChatRoom::checkin`] - 聊天室打卡，领取昨日活跃奖励并查询当前活跃度。
+//! - [`ChatRoom::emoji_packs`] - 获取表情包列表。
+//! - [`ChatRoom::barrager`] - 发送弹幕，返回新消息的 oId。
 //! - [`ChatRoom::barrage_cost`] - 获取弹幕花费。
 //! - [`ChatRoom::mutes`] - 获取禁言成员列表。
 //! - [`ChatRoom::get_raw_message`] - 获取消息原文。
+//! - [`ChatRoom::message_url`] - 获取消息的分享链接。
 //!
 //! # 示例
 //!
@@ -94,33 +115,45 @@
 //! 聊天室支持以下事件类型（通过特定 `on_*` 方法监听）：
 //!
 //! - `Online` - 在线用户更新。
+//! - `OnlineChanged` - 在线用户进出（相对上一次在线列表的变化）。
 //! - `DiscussChanged` - 话题修改。
 //! - `Revoke` - 消息撤回。
 //! - `Msg` - 普通消息。
+//! - `Edit` - 消息编辑（相同 oId 的消息再次推送）。
 //! - `Barrager` - 弹幕消息。
 //! - `RedPacket` - 红包消息。
 //! - `RedPacketStatus` - 红包状态。
 //! - `Music` - 音乐消息。
 //! - `Weather` - 天气消息。
 //! - `Custom` - 进出场消息。
+//! - `Unknown` - SDK 尚未支持的消息类型。
 //! - `All` - 所有事件（除了自身）。
 
+use crate::api::session::Session;
+use crate::api::user::{claim_liveness_reward, fetch_liveness, fetch_liveness_collected};
 use crate::api::ws::{
     ParsedMessageHandler, RetryPolicy, WebSocketError, WsConnection, WsLogHook, build_ws_url,
 };
 use crate::model::MuteItem;
 use crate::model::chatroom::{
-    BarragerCost, BarragerMsg, ChatContentType, ChatReactionMsg, ChatRoomMessageMode,
-    ChatRoomMessageType, ChatRoomMsg, ClientType, CustomMsg, OnlineInfo, RevokeMsg,
+    BarragerCost, BarragerMsg, ChatContentType, ChatMsgKind, ChatReactionMsg, ChatRoomMessageMode,
+    ChatRoomMessageType, ChatRoomMsg, ClientType, CustomMsg, MusicMsg, OnlineInfo, RevokeMsg,
+    SendOptions, WeatherMsg,
 };
+use crate::model::emoji::EmojiPack;
 use crate::model::reaction::ReactionMutationResult;
-use crate::model::redpacket::RedPacketStatusMsg;
-use crate::utils::get_text;
+use crate::model::redpacket::{RedPacketMessage, RedPacketStatusMsg};
+use crate::model::user::LivenessReward;
+use crate::utils::{
+    LONG_TIMEOUT, as_array_or_empty, base_url, get_text, get_with_timeout, strip_raw_comment,
+    ws_domain,
+};
 use crate::utils::{build_http_path, delete, error::Error, get, post};
 use serde_json::{Value, json};
+use std::collections::{HashSet, VecDeque};
 use std::str::FromStr;
-use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
 use url::Url;
 
 #[derive(Debug, Clone, serde::Deserialize)]
@@ -150,26 +183,35 @@ pub enum ChatRoomEventData {
         discussing: Option<String>,
         online_chat_cnt: Option<usize>,
     },
+    /// 在线用户进出，相对上一次在线列表新增与离开的用户
+    OnlineChanged {
+        joined: Vec<OnlineInfo>,
+        left: Vec<OnlineInfo>,
+    },
     /// 话题修改
     DiscussChanged(String),
     /// 消息撤回
     Revoke(String),
     /// 普通消息
     Msg(ChatRoomMsg),
+    /// 消息编辑，与已收到消息相同 oId 的重发
+    Edit(ChatRoomMsg),
     /// 弹幕消息
     Barrager(BarragerMsg),
     /// 红包消息
-    RedPacket(ChatRoomMsg<Value>),
+    RedPacket(ChatRoomMsg<RedPacketMessage>),
     /// 红包状态
     RedPacketStatus(RedPacketStatusMsg),
     /// 音乐消息
-    Music(ChatRoomMsg<Value>),
+    Music(ChatRoomMsg<MusicMsg>),
     /// 天气消息
-    Weather(ChatRoomMsg<Value>),
+    Weather(ChatRoomMsg<WeatherMsg>),
     /// 进出场消息
     Custom(CustomMsg),
     /// 聊天室表态/反应
     ChatReaction(ChatReactionMsg),
+    /// SDK 尚未支持的消息类型，保留原始数据
+    Unknown { type_: String, raw: Value },
 }
 
 /// 聊天室事件类型枚举
@@ -177,12 +219,16 @@ pub enum ChatRoomEventData {
 pub enum ChatRoomEventType {
     /// 在线用户更新
     Online,
+    /// 在线用户进出
+    OnlineChanged,
     /// 话题修改
     DiscussChanged,
     /// 消息撤回
     Revoke,
     /// 普通消息
     Msg,
+    /// 消息编辑
+    Edit,
     /// 弹幕消息
     Barrager,
     /// 红包消息
@@ -197,10 +243,57 @@ pub enum ChatRoomEventType {
     Custom,
     /// 聊天室表态/反应
     ChatReaction,
+    /// 未知消息类型
+    Unknown,
     /// 所有事件（除了自身）
     All,
 }
 
+impl ChatRoomEventType {
+    /// 事件类型名称，与服务端消息 `type` 保持一致
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChatRoomEventType::Online => "online",
+            ChatRoomEventType::OnlineChanged => "onlineChanged",
+            ChatRoomEventType::DiscussChanged => "discussChanged",
+            ChatRoomEventType::Revoke => "revoke",
+            ChatRoomEventType::Msg => "msg",
+            ChatRoomEventType::Edit => "edit",
+            ChatRoomEventType::Barrager => "barrager",
+            ChatRoomEventType::RedPacket => "redPacket",
+            ChatRoomEventType::RedPacketStatus => "redPacketStatus",
+            ChatRoomEventType::Music => "music",
+            ChatRoomEventType::Weather => "weather",
+            ChatRoomEventType::Custom => "customMessage",
+            ChatRoomEventType::ChatReaction => "chatreaction",
+            ChatRoomEventType::Unknown => "unknown",
+            ChatRoomEventType::All => "all",
+        }
+    }
+}
+
+impl ChatRoomEventData {
+    /// 事件数据对应的事件类型
+    pub fn event_type(&self) -> ChatRoomEventType {
+        match self {
+            ChatRoomEventData::Online { .. } => ChatRoomEventType::Online,
+            ChatRoomEventData::OnlineChanged { .. } => ChatRoomEventType::OnlineChanged,
+            ChatRoomEventData::DiscussChanged(_) => ChatRoomEventType::DiscussChanged,
+            ChatRoomEventData::Revoke(_) => ChatRoomEventType::Revoke,
+            ChatRoomEventData::Msg(_) => ChatRoomEventType::Msg,
+            ChatRoomEventData::Edit(_) => ChatRoomEventType::Edit,
+            ChatRoomEventData::Barrager(_) => ChatRoomEventType::Barrager,
+            ChatRoomEventData::RedPacket(_) => ChatRoomEventType::RedPacket,
+            ChatRoomEventData::RedPacketStatus(_) => ChatRoomEventType::RedPacketStatus,
+            ChatRoomEventData::Music(_) => ChatRoomEventType::Music,
+            ChatRoomEventData::Weather(_) => ChatRoomEventType::Weather,
+            ChatRoomEventData::Custom(_) => ChatRoomEventType::Custom,
+            ChatRoomEventData::ChatReaction(_) => ChatRoomEventType::ChatReaction,
+            ChatRoomEventData::Unknown { .. } => ChatRoomEventType::Unknown,
+        }
+    }
+}
+
 /// 聊天室事件监听器类型
 pub type ChatRoomListener = Arc<dyn Fn(ChatRoomEventData) + Send + Sync + 'static>;
 
@@ -213,8 +306,15 @@ fn parse_chatroom_message(json: &Value) -> Result<(ChatRoomEventType, ChatRoomEv
     let type_str = json["type"]
         .as_str()
         .ok_or_else(|| Error::Parse("Missing type in message".to_string()))?;
-    let r#type = ChatRoomMessageType::from_str(type_str)
-        .map_err(|_| Error::Parse(format!("Unknown message type: {}", type_str)))?;
+    let Ok(r#type) = ChatRoomMessageType::from_str(type_str) else {
+        return Ok((
+            ChatRoomEventType::Unknown,
+            ChatRoomEventData::Unknown {
+                type_: type_str.to_string(),
+                raw: json.clone(),
+            },
+        ));
+    };
 
     match r#type {
         ChatRoomMessageType::Online => {
@@ -267,31 +367,10 @@ fn parse_chatroom_message(json: &Value) -> Result<(ChatRoomEventType, ChatRoomEv
                 .to_string();
             Ok((ChatRoomEventType::Revoke, ChatRoomEventData::Revoke(o_id)))
         }
-        ChatRoomMessageType::Msg => {
-            let chat_msg = ChatRoomMsg::from_value(json)?;
-
-            // 检查 content 是否为 music 或 weather
-            if let Value::Object(ref obj) = chat_msg.content {
-                if obj.get("msgType").and_then(|v| v.as_str()) == Some("music") {
-                    Ok((ChatRoomEventType::Music, ChatRoomEventData::Music(chat_msg)))
-                } else if obj.get("msgType").and_then(|v| v.as_str()) == Some("weather") {
-                    Ok((
-                        ChatRoomEventType::Weather,
-                        ChatRoomEventData::Weather(chat_msg),
-                    ))
-                } else {
-                    Ok((ChatRoomEventType::Msg, ChatRoomEventData::Msg(chat_msg)))
-                }
-            } else {
-                Ok((ChatRoomEventType::Msg, ChatRoomEventData::Msg(chat_msg)))
-            }
-        }
-        ChatRoomMessageType::RedPacket => {
-            let redpacket_msg = ChatRoomMsg::from_value(json)?;
-            Ok((
-                ChatRoomEventType::RedPacket,
-                ChatRoomEventData::RedPacket(redpacket_msg),
-            ))
+        ChatRoomMessageType::Msg | ChatRoomMessageType::RedPacket => {
+            let msg = ChatRoomMsg::from_value(json)?;
+            let kind = msg.classify();
+            Ok(content_event(msg, kind))
         }
         ChatRoomMessageType::Barrager => {
             let barrager = BarragerMsg::from_value(json)?;
@@ -324,36 +403,466 @@ fn parse_chatroom_message(json: &Value) -> Result<(ChatRoomEventType, ChatRoomEv
     }
 }
 
+/// 记录最近消息 oId 的数量上限
+const SEEN_OID_CAPACITY: usize = 1000;
+
+/// 最近收到的消息 oId，用于识别消息编辑与补发去重
+#[derive(Default)]
+struct SeenOids {
+    set: HashSet<String>,
+    order: VecDeque<String>,
+    /// 由补发派发、尚未收到实时推送的 oId
+    replayed: HashSet<String>,
+}
+
+/// 实时推送的消息 oId 此前的派发情况
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SeenState {
+    /// 首次收到
+    New,
+    /// 已经派发过，再次推送视为编辑
+    Delivered,
+    /// 已由补发派发，实时推送的副本应丢弃
+    Replayed,
+}
+
+impl SeenOids {
+    /// 记录实时推送的 oId，返回此前的派发情况
+    fn check_and_insert(&mut self, o_id: &str) -> SeenState {
+        if self.replayed.remove(o_id) {
+            return SeenState::Replayed;
+        }
+        if self.set.contains(o_id) {
+            return SeenState::Delivered;
+        }
+        self.insert(o_id);
+        SeenState::New
+    }
+
+    /// 记录补发的 oId，返回是否需要派发（尚未派发过）
+    fn insert_replayed(&mut self, o_id: &str) -> bool {
+        if self.set.contains(o_id) {
+            return false;
+        }
+        self.insert(o_id);
+        self.replayed.insert(o_id.to_string());
+        true
+    }
+
+    fn insert(&mut self, o_id: &str) {
+        if self.order.len() >= SEEN_OID_CAPACITY
+            && let Some(old) = self.order.pop_front()
+        {
+            self.set.remove(&old);
+            self.replayed.remove(&old);
+        }
+        self.set.insert(o_id.to_string());
+        self.order.push_back(o_id.to_string());
+    }
+}
+
+/// 带 oId 的聊天内容事件的 oId，其它事件返回 `None`
+fn content_oid(event: &ChatRoomEventData) -> Option<&str> {
+    match event {
+        ChatRoomEventData::Msg(msg) | ChatRoomEventData::Edit(msg) => Some(&msg.oId),
+        ChatRoomEventData::RedPacket(msg) => Some(&msg.oId),
+        ChatRoomEventData::Music(msg) => Some(&msg.oId),
+        ChatRoomEventData::Weather(msg) => Some(&msg.oId),
+        _ => None,
+    }
+}
+
+/// 将相同 oId 的重复普通消息转换为编辑事件，已由补发派发过的消息返回 `None`
+fn mark_edits(
+    seen: &StdMutex<SeenOids>,
+    event_type: ChatRoomEventType,
+    event: ChatRoomEventData,
+) -> Option<(ChatRoomEventType, ChatRoomEventData)> {
+    let Some(o_id) = content_oid(&event) else {
+        return Some((event_type, event));
+    };
+    let state = seen
+        .lock()
+        .map(|mut seen| seen.check_and_insert(o_id))
+        .unwrap_or(SeenState::New);
+    match (state, event) {
+        (SeenState::Replayed, _) => None,
+        (SeenState::Delivered, ChatRoomEventData::Msg(msg)) => {
+            Some((ChatRoomEventType::Edit, ChatRoomEventData::Edit(msg)))
+        }
+        (_, event) => Some((event_type, event)),
+    }
+}
+
+/// 断线重连后补发消息的最大条数
+const REPLAY_SIZE: u32 = 100;
+
+/// 记录收到的最新消息 oId，用于断线重连后补发
+fn record_last_oid(last_oid: &StdMutex<Option<String>>, event: &ChatRoomEventData) {
+    let Some(o_id) = content_oid(event) else {
+        return;
+    };
+    if let Ok(mut guard) = last_oid.lock()
+        && guard.as_ref().is_none_or(|last| is_newer_oid(o_id, last))
+    {
+        *guard = Some(o_id.to_string());
+    }
+}
+
+/// 比较消息 oId，oId 为创建时间戳，按数值比较
+fn is_newer_oid(o_id: &str, than: &str) -> bool {
+    match (o_id.parse::<u64>(), than.parse::<u64>()) {
+        (Ok(a), Ok(b)) => a > b,
+        _ => o_id > than,
+    }
+}
+
+/// 按消息内容将聊天消息归类为对应的事件，并解析音乐、天气、红包、弹幕等特殊内容
+///
+/// 实时消息按 [`ChatRoomMsg::classify`] 分类，补发的历史消息按 [`ChatRoomMsg::classify_history`] 分类；
+/// 特殊内容解析失败时按普通消息处理，保留原始内容，避免整条消息被丢弃。
+fn content_event(msg: ChatRoomMsg, kind: ChatMsgKind) -> (ChatRoomEventType, ChatRoomEventData) {
+    match kind {
+        ChatMsgKind::Text(_) => (ChatRoomEventType::Msg, ChatRoomEventData::Msg(msg)),
+        ChatMsgKind::Music(music) => (
+            ChatRoomEventType::Music,
+            ChatRoomEventData::Music(msg.map_content(|_| music)),
+        ),
+        ChatMsgKind::Weather(weather) => (
+            ChatRoomEventType::Weather,
+            ChatRoomEventData::Weather(msg.map_content(|_| weather)),
+        ),
+        ChatMsgKind::RedPacket(red_packet) => (
+            ChatRoomEventType::RedPacket,
+            ChatRoomEventData::RedPacket(msg.map_content(|_| red_packet)),
+        ),
+        ChatMsgKind::Barrager { color, content } => (
+            ChatRoomEventType::Barrager,
+            ChatRoomEventData::Barrager(BarragerMsg {
+                userName: msg.userName,
+                userNickname: msg.userNickname,
+                barragerContent: content,
+                barragerColor: color,
+                userAvatarURL200: msg.userAvatarURL.clone(),
+                userAvatarURL48: msg.userAvatarURL.clone(),
+                userAvatarURL210: msg.userAvatarURL.clone(),
+                userAvatarURL: msg.userAvatarURL,
+            }),
+        ),
+    }
+}
+
+/// 补发 `since` 之后、尚未派发过的消息，完成后派发补发期间暂存的实时事件
+///
+/// 补发的消息直接派发给监听器，不经过编辑识别；之后收到的实时推送副本会被丢弃。
+/// 补发失败或达到 [`REPLAY_SIZE`] 上限时通过 `log_hook` 报告，暂存的实时事件照常派发。
+async fn replay_missed(
+    api_key: String,
+    since: String,
+    live: LiveState,
+    handler: ChatRoomHandler,
+    log_hook: Option<WsLogHook>,
+) {
+    let emitter = handler.get_emitter();
+    match fetch_messages_after(&api_key, &since, &ChatContentType::Html).await {
+        Ok(messages) => {
+            if messages.len() >= REPLAY_SIZE as usize
+                && let Some(hook) = &log_hook
+            {
+                hook(&format!(
+                    "Chatroom replay reached {} messages, later missed messages may be lost",
+                    REPLAY_SIZE
+                ));
+            }
+            for msg in messages {
+                let fresh = live
+                    .seen
+                    .lock()
+                    .map(|mut seen| seen.insert_replayed(&msg.oId))
+                    .unwrap_or(false);
+                if !fresh {
+                    continue;
+                }
+                let kind = msg.classify_history();
+                let (event_type, event) = content_event(msg, kind);
+                record_last_oid(&live.last_oid, &event);
+                emitter
+                    .emit(&event_type, event, Some(&ChatRoomEventType::All))
+                    .await;
+            }
+        }
+        Err(err) => {
+            if let Some(hook) = &log_hook {
+                hook(&format!("Chatroom replay failed: {}", err));
+            }
+        }
+    }
+
+    while let Some(held) = live.take_held() {
+        for (event_type, event) in held {
+            for (event_type, event) in live.process(event_type, event) {
+                emitter
+                    .emit(&event_type, event, Some(&ChatRoomEventType::All))
+                    .await;
+            }
+        }
+    }
+}
+
+/// 获取指定消息之后的消息，按时间升序，不包含该消息本身
+async fn fetch_messages_after(
+    api_key: &str,
+    o_id: &str,
+    type_: &ChatContentType,
+) -> Result<Vec<ChatRoomMsg>, Error> {
+    let mut messages = fetch_msg_around(
+        api_key,
+        o_id,
+        ChatRoomMessageMode::After,
+        REPLAY_SIZE,
+        type_,
+    )
+    .await?;
+    messages.retain(|msg| is_newer_oid(&msg.oId, o_id));
+    messages.sort_by(|a, b| {
+        if is_newer_oid(&a.oId, &b.oId) {
+            std::cmp::Ordering::Greater
+        } else if is_newer_oid(&b.oId, &a.oId) {
+            std::cmp::Ordering::Less
+        } else {
+            std::cmp::Ordering::Equal
+        }
+    });
+    Ok(messages)
+}
+
+/// 请求指定消息附近的聊天室消息
+async fn fetch_msg_around(
+    api_key: &str,
+    o_id: &str,
+    mode: ChatRoomMessageMode,
+    size: u32,
+    type_: &ChatContentType,
+) -> Result<Vec<ChatRoomMsg>, Error> {
+    let resp = get(&build_http_path(
+        "chat-room/getMessage",
+        &[
+            ("oId", o_id.to_string()),
+            ("mode", mode.to_string()),
+            ("size", size.to_string()),
+            ("type", type_.as_str().to_string()),
+            ("apiKey", api_key.to_string()),
+        ],
+    ))
+    .await?;
+
+    if let Some(code) = resp["code"].as_i64()
+        && code != 0
+    {
+        return Err(Error::from_response(&resp, "Api error"));
+    }
+
+    parse_history(&resp["data"], type_)
+}
+
+/// 根据推送同步话题与在线用户缓存
+///
+/// `online` 消息携带的 `discussing` 与缓存不同时，额外派发一个 `DiscussChanged` 事件，
+/// 使刚连接的客户端也能拿到当前话题；在线用户与上一次列表相比有进出时，额外派发
+/// `OnlineChanged` 事件。连接后的第一份在线列表只更新缓存，不视为进出。
+fn sync_state(
+    discuss: &StdMutex<String>,
+    onlines: &StdMutex<Vec<OnlineInfo>>,
+    event_type: ChatRoomEventType,
+    event: ChatRoomEventData,
+) -> Vec<(ChatRoomEventType, ChatRoomEventData)> {
+    let mut changed = None;
+    let mut online_diff = None;
+    match &event {
+        ChatRoomEventData::Online {
+            users, discussing, ..
+        } => {
+            if !users.is_empty() {
+                let mut guard = onlines
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+                if !guard.is_empty() {
+                    online_diff = Some(diff_onlines(&guard, users));
+                }
+                *guard = users.clone();
+            }
+            if let Some(topic) = discussing {
+                let mut guard = discuss
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+                if *guard != *topic {
+                    *guard = topic.clone();
+                    changed = Some(topic.clone());
+                }
+            }
+        }
+        ChatRoomEventData::DiscussChanged(topic) => {
+            *discuss
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()) = topic.clone();
+        }
+        _ => {}
+    }
+
+    let mut events = vec![(event_type, event)];
+    if let Some((joined, left)) = online_diff
+        && (!joined.is_empty() || !left.is_empty())
+    {
+        events.push((
+            ChatRoomEventType::OnlineChanged,
+            ChatRoomEventData::OnlineChanged { joined, left },
+        ));
+    }
+    if let Some(topic) = changed {
+        events.push((
+            ChatRoomEventType::DiscussChanged,
+            ChatRoomEventData::DiscussChanged(topic),
+        ));
+    }
+    events
+}
+
+/// 按用户名比较前后两份在线列表，返回 (新加入, 已离开) 的用户
+fn diff_onlines(before: &[OnlineInfo], after: &[OnlineInfo]) -> (Vec<OnlineInfo>, Vec<OnlineInfo>) {
+    let before_names: HashSet<&str> = before.iter().map(|u| u.userName.as_str()).collect();
+    let after_names: HashSet<&str> = after.iter().map(|u| u.userName.as_str()).collect();
+    let joined = after
+        .iter()
+        .filter(|u| !before_names.contains(u.userName.as_str()))
+        .cloned()
+        .collect();
+    let left = before
+        .iter()
+        .filter(|u| !after_names.contains(u.userName.as_str()))
+        .cloned()
+        .collect();
+    (joined, left)
+}
+
+/// 暂存的聊天室事件
+type HeldEvents = Vec<(ChatRoomEventType, ChatRoomEventData)>;
+
+/// 实时事件派发前的处理状态
+///
+/// 断线重连补发期间暂存实时事件，补发完成后再按收到的顺序派发，保证补发的消息先于实时消息。
+#[derive(Clone, Default)]
+struct LiveState {
+    discuss: Arc<StdMutex<String>>,
+    onlines: Arc<StdMutex<Vec<OnlineInfo>>>,
+    seen: Arc<StdMutex<SeenOids>>,
+    last_oid: Arc<StdMutex<Option<String>>>,
+    /// 补发期间暂存的实时事件，`None` 表示未在补发
+    held: Arc<StdMutex<Option<HeldEvents>>>,
+}
+
+impl LiveState {
+    /// 事件转换钩子：补发期间暂存事件，否则按 [`LiveState::process`] 处理
+    fn transform(
+        &self,
+        event_type: ChatRoomEventType,
+        event: ChatRoomEventData,
+    ) -> Vec<(ChatRoomEventType, ChatRoomEventData)> {
+        if let Some(held) = self
+            .held
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .as_mut()
+        {
+            held.push((event_type, event));
+            return Vec::new();
+        }
+        self.process(event_type, event)
+    }
+
+    /// 识别编辑、记录最新 oId 并同步话题与在线列表
+    fn process(
+        &self,
+        event_type: ChatRoomEventType,
+        event: ChatRoomEventData,
+    ) -> Vec<(ChatRoomEventType, ChatRoomEventData)> {
+        let Some((event_type, event)) = mark_edits(&self.seen, event_type, event) else {
+            return Vec::new();
+        };
+        record_last_oid(&self.last_oid, &event);
+        sync_state(&self.discuss, &self.onlines, event_type, event)
+    }
+
+    /// 开始暂存实时事件
+    fn hold(&self) {
+        self.held
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get_or_insert_with(Vec::new);
+    }
+
+    /// 取出暂存的实时事件，没有暂存事件时恢复实时派发并返回 `None`
+    fn take_held(&self) -> Option<HeldEvents> {
+        let mut guard = self
+            .held
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        match guard.as_mut() {
+            Some(held) if !held.is_empty() => Some(std::mem::take(held)),
+            _ => {
+                *guard = None;
+                None
+            }
+        }
+    }
+
+    /// 最近收到的消息 oId
+    fn last_oid(&self) -> Option<String> {
+        self.last_oid.lock().ok().and_then(|guard| guard.clone())
+    }
+}
+
 /// 聊天室客户端
 pub struct ChatRoom {
     connection: WsConnection,
     handler: ChatRoomHandler,
-    api_key: String,
-    discuss: Arc<Mutex<String>>,
-    onlines: Arc<Mutex<Vec<OnlineInfo>>>,
     client: ClientType,
     version: String,
+    session: Arc<Session>,
+    live: LiveState,
+    log_hook: Option<WsLogHook>,
 }
 
 impl ChatRoom {
     pub fn new(api_key: String) -> Self {
+        Self::with_session(Session::new(api_key))
+    }
+
+    /// 使用共享的登录会话创建实例，API key 从会话读取
+    pub fn with_session(session: Arc<Session>) -> Self {
+        let mut handler = ChatRoomHandler::new(
+            parse_chatroom_message,
+            Some(ChatRoomEventType::All),
+            "chatroom",
+        );
+        let live = LiveState::default();
+        {
+            let live = live.clone();
+            handler.set_transform(move |event_type, event| live.transform(event_type, event));
+        }
+
         Self {
             connection: WsConnection::new(),
-            handler: ChatRoomHandler::new(
-                parse_chatroom_message,
-                Some(ChatRoomEventType::All),
-                "chatroom",
-            ),
-            api_key,
-            discuss: Arc::new(Mutex::new(String::new())),
-            onlines: Arc::new(Mutex::new(Vec::new())),
+            handler,
             client: ClientType::Rust,
             version: env!("CARGO_PKG_VERSION").to_string(),
+            session,
+            live,
+            log_hook: None,
         }
     }
 
     pub async fn get_node(&self) -> Result<ChatRoomNodeResponse, WebSocketError> {
-        let url = build_http_path("chat-room/node/get", &[("apiKey", self.api_key.clone())]);
+        let url = build_http_path("chat-room/node/get", &[("apiKey", self.session.api_key())]);
 
         let response: Value = get(&url)
             .await
@@ -382,9 +891,9 @@ impl ChatRoom {
                 Ok(parsed.to_string())
             }
             Err(_) => build_ws_url(
-                "fishpi.cn",
+                &ws_domain(),
                 "chat-room-channel",
-                &[("apiKey", self.api_key.clone())],
+                &[("apiKey", self.session.api_key())],
             ),
         }
     }
@@ -395,6 +904,7 @@ impl ChatRoom {
     /// * `reload` - 是否重新连接
     pub async fn connect(&mut self, reload: bool) -> Result<(), WebSocketError> {
         let url = self.get_ws_url().await?;
+        self.install_replay_hook(false);
         self.connection
             .connect(reload, &url, self.handler.clone())
             .await
@@ -403,29 +913,80 @@ impl ChatRoom {
     /// 重连
     pub async fn reconnect(&mut self) -> Result<(), WebSocketError> {
         let url = self.get_ws_url().await?;
+        self.install_replay_hook(true);
         self.connection.reconnect(&url, self.handler.clone()).await
     }
 
+    /// 重连成功后，补发最近收到的消息之后错过的消息
+    ///
+    /// 首次连接不补发，自动重连与 [`ChatRoom::reconnect`] 成功后补发。
+    /// 连接打开时立即记下最近收到的消息 oId，补发完成前收到的实时事件会暂存，
+    /// 补发完成后再派发，已派发过的消息会跳过；断线期间超过 100 条的消息无法全部补发，
+    /// 此时与补发失败一样通过 [`ChatRoom::on_ws_log`] 报告。
+    ///
+    /// - `replay_on_first_open` 本次连接首次打开时是否也补发
+    fn install_replay_hook(&mut self, replay_on_first_open: bool) {
+        let session = Arc::clone(&self.session);
+        let live = self.live.clone();
+        let handler = self.handler.clone();
+        let log_hook = self.log_hook.clone();
+        self.connection.set_open_hook(move |reconnected| {
+            if !reconnected && !replay_on_first_open {
+                return;
+            }
+            let Some(since) = live.last_oid() else {
+                return;
+            };
+            live.hold();
+            tokio::spawn(replay_missed(
+                session.api_key(),
+                since,
+                live.clone(),
+                handler.clone(),
+                log_hook.clone(),
+            ));
+        });
+    }
+
+    /// 是否已连接
+    pub fn is_connected(&self) -> bool {
+        self.connection.is_connected()
+    }
+
     pub fn set_reconnect_policy(&mut self, policy: RetryPolicy) {
         self.connection.set_retry_policy(policy);
     }
 
+    /// 设置心跳间隔，默认 60 秒，`None` 表示不发送心跳，下次连接时生效
+    pub fn set_heartbeat_interval(&mut self, heartbeat_interval: Option<Duration>) {
+        self.connection.set_heartbeat_interval(heartbeat_interval);
+    }
+
     pub fn on_ws_log<F>(&mut self, hook: F)
     where
         F: Fn(&str) + Send + Sync + 'static,
     {
         let hook = Arc::new(hook) as WsLogHook;
+        self.log_hook = Some(hook.clone());
         self.connection.set_log_hook_arc(hook.clone());
         self.handler.set_log_hook_arc(hook);
     }
 
+    /// 监听重连状态，每次重连前调用，参数为 (第几次尝试, 重连前的等待时间)
+    ///
+    /// 可用于在界面上显示“重连中”，与连接彻底失败区分开。下次连接时生效。
+    pub fn on_reconnecting<F>(&mut self, hook: F)
+    where
+        F: Fn(u32, Duration) + Send + Sync + 'static,
+    {
+        self.connection.set_reconnect_hook(hook);
+    }
+
     /// 监听在线用户更新事件（同时可获取 discussing / onlineChatCnt）
     pub async fn on_online<F>(&self, listener: F)
     where
         F: Fn(Vec<OnlineInfo>, Option<String>, Option<usize>) + Send + Sync + 'static,
     {
-        let onlines = Arc::clone(&self.onlines);
-        let discuss = Arc::clone(&self.discuss);
         let wrapped_listener: ChatRoomListener = Arc::new(move |event: ChatRoomEventData| {
             if let ChatRoomEventData::Online {
                 users,
@@ -433,17 +994,6 @@ impl ChatRoom {
                 online_chat_cnt,
             } = event
             {
-                // 更新状态
-                if !users.is_empty()
-                    && let Ok(mut onlines_guard) = onlines.try_lock()
-                {
-                    *onlines_guard = users.clone();
-                }
-                if let Some(topic) = discussing.as_ref()
-                    && let Ok(mut discuss_guard) = discuss.try_lock()
-                {
-                    *discuss_guard = topic.to_string();
-                }
                 listener(users, discussing, online_chat_cnt);
             }
         });
@@ -455,18 +1005,31 @@ impl ChatRoom {
             .await;
     }
 
+    /// 监听在线用户进出事件
+    ///
+    /// 回调参数为 (新加入的用户, 已离开的用户)，由相邻两次在线列表推送比较得出。
+    pub async fn on_online_changed<F>(&self, listener: F)
+    where
+        F: Fn(Vec<OnlineInfo>, Vec<OnlineInfo>) + Send + Sync + 'static,
+    {
+        self.add_listener(
+            ChatRoomEventType::OnlineChanged,
+            move |event: ChatRoomEventData| {
+                if let ChatRoomEventData::OnlineChanged { joined, left } = event {
+                    listener(joined, left);
+                }
+            },
+        )
+        .await;
+    }
+
     /// 监听话题变更事件
     pub async fn on_discuss<F>(&self, listener: F)
     where
         F: Fn(String) + Send + Sync + 'static,
     {
-        let discuss = Arc::clone(&self.discuss);
         let wrapped_listener: ChatRoomListener = Arc::new(move |event: ChatRoomEventData| {
             if let ChatRoomEventData::DiscussChanged(topic) = event {
-                // 更新状态
-                if let Ok(mut discuss_guard) = discuss.try_lock() {
-                    *discuss_guard = topic.clone();
-                }
                 listener(topic);
             }
         });
@@ -507,6 +1070,19 @@ impl ChatRoom {
         .await;
     }
 
+    /// 监听消息编辑事件
+    pub async fn on_edit<F>(&self, listener: F)
+    where
+        F: Fn(ChatRoomMsg) + Send + Sync + 'static,
+    {
+        self.add_listener(ChatRoomEventType::Edit, move |event: ChatRoomEventData| {
+            if let ChatRoomEventData::Edit(msg) = event {
+                listener(msg);
+            }
+        })
+        .await;
+    }
+
     /// 监听弹幕消息事件
     pub async fn on_barrager<F>(&self, listener: F)
     where
@@ -526,7 +1102,7 @@ impl ChatRoom {
     /// 监听红包消息事件
     pub async fn on_redpacket<F>(&self, listener: F)
     where
-        F: Fn(ChatRoomMsg<Value>) + Send + Sync + 'static,
+        F: Fn(ChatRoomMsg<RedPacketMessage>) + Send + Sync + 'static,
     {
         self.add_listener(
             ChatRoomEventType::RedPacket,
@@ -558,7 +1134,7 @@ impl ChatRoom {
     /// 监听音乐消息事件
     pub async fn on_music<F>(&self, listener: F)
     where
-        F: Fn(ChatRoomMsg<Value>) + Send + Sync + 'static,
+        F: Fn(ChatRoomMsg<MusicMsg>) + Send + Sync + 'static,
     {
         self.add_listener(ChatRoomEventType::Music, move |event: ChatRoomEventData| {
             if let ChatRoomEventData::Music(music) = event {
@@ -571,7 +1147,7 @@ impl ChatRoom {
     /// 监听天气消息事件
     pub async fn on_weather<F>(&self, listener: F)
     where
-        F: Fn(ChatRoomMsg<Value>) + Send + Sync + 'static,
+        F: Fn(ChatRoomMsg<WeatherMsg>) + Send + Sync + 'static,
     {
         self.add_listener(
             ChatRoomEventType::Weather,
@@ -607,6 +1183,46 @@ impl ChatRoom {
         self.add_listener(ChatRoomEventType::All, listener).await;
     }
 
+    /// 监听 SDK 尚未支持的消息类型，参数为 (消息类型, 原始数据)
+    pub async fn on_unknown<F>(&self, listener: F)
+    where
+        F: Fn(String, Value) + Send + Sync + 'static,
+    {
+        self.add_listener(
+            ChatRoomEventType::Unknown,
+            move |event: ChatRoomEventData| {
+                if let ChatRoomEventData::Unknown { type_, raw } = event {
+                    listener(type_, raw);
+                }
+            },
+        )
+        .await;
+    }
+
+    /// SDK 已支持的聊天室消息类型
+    ///
+    /// 服务端没有提供消息类型元数据接口，这里返回 SDK 构建时已知的类型；
+    /// 服务端新增的类型会以 [`ChatRoomEventData::Unknown`] 派发，可通过 [`ChatRoom::on_unknown`] 监听。
+    pub fn supported_message_types() -> Vec<String> {
+        ChatRoomMessageType::values()
+            .into_iter()
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// 监听所有事件，同时获得事件类型名称
+    ///
+    /// 类型名称见 [`ChatRoomEventType::as_str`]，适合日志、统计等通用处理。
+    pub async fn on_all_typed<F>(&self, listener: F)
+    where
+        F: Fn(&str, ChatRoomEventData) + Send + Sync + 'static,
+    {
+        self.add_listener(ChatRoomEventType::All, move |event: ChatRoomEventData| {
+            listener(event.event_type().as_str(), event);
+        })
+        .await;
+    }
+
     async fn add_listener<F>(&self, event: ChatRoomEventType, listener: F)
     where
         F: Fn(ChatRoomEventData) + Send + Sync + 'static,
@@ -630,17 +1246,61 @@ impl ChatRoom {
         self.connection.disconnect();
     }
 
+    /// 通过已连接的聊天室 WebSocket 直接发送原始 JSON
+    ///
+    /// 该方法不做任何校验，原样序列化后发送，适用于 SDK 尚未封装的新消息类型。
+    /// 需要先调用 [`ChatRoom::connect`] 建立连接。
+    ///
+    /// - `json` 要发送的 JSON 数据
+    pub fn send_raw(&self, json: Value) -> Result<(), Error> {
+        self.connection
+            .send_text(&json.to_string())
+            .map_err(|e| Error::api(format!("WS send failed: {}", e)))
+    }
+
+    /// 通过已连接的聊天室 WebSocket 发送消息，不再发起 HTTP 请求
+    ///
+    /// 需要先调用 [`ChatRoom::connect`] 建立连接，连接已断开时返回错误；
+    /// 发送结果不会有服务端回执，需要确认送达时请使用 [`ChatRoom::send`]。
+    ///
+    /// # 参数
+    /// * `msg` - 消息内容，同样支持 `[setdiscuss]` 等指令
+    pub fn send_ws(&self, msg: String) -> Result<(), Error> {
+        self.connection
+            .send_text(&msg)
+            .map_err(|e| Error::api(format!("WS send failed: {}", e)))
+    }
+
     /// 发送消息
     ///
+    /// 以 Markdown 发送，来源标识使用 [`ChatRoom::set_client_type`] 设置的客户端类型与版本
+    ///
     /// # 参数
     /// * `msg` - 消息内容
-    pub async fn send(&self, msg: String) -> Result<(), Error> {
-        let client = format!("{}/{}", self.client.as_str(), self.version);
+    ///
+    /// 返回新消息的 oId，可用于 [`ChatRoom::revoke`] 或与推送回来的消息对应；
+    /// 服务端发送成功但响应中没有 oId 时返回 `None`
+    pub async fn send(&self, msg: String) -> Result<Option<String>, Error> {
+        let options = SendOptions::new(self.client.clone()).version(self.version.clone());
+        self.send_with(msg, options).await
+    }
 
+    /// 按指定选项发送消息
+    ///
+    /// # 参数
+    /// * `msg` - 消息内容
+    /// * `options` - 发送选项 [SendOptions]，可指定纯文本发送及客户端标识
+    ///
+    /// 返回新消息的 oId，响应中没有 oId 时返回 `None`
+    pub async fn send_with(
+        &self,
+        msg: String,
+        options: SendOptions,
+    ) -> Result<Option<String>, Error> {
         let data = json!({
-            "content": msg,
-            "client": client,
-            "apiKey": self.api_key,
+            "content": options.render(&msg),
+            "client": options.client_tag(),
+            "apiKey": self.session.api_key(),
         });
 
         let resp = post("chat-room/send", Some(data)).await?;
@@ -648,18 +1308,21 @@ impl ChatRoom {
         if let Some(code) = resp["code"].as_i64()
             && code != 0
         {
-            return Err(Error::Api(
-                resp["msg"].as_str().unwrap_or("发送失败").to_string(),
-            ));
+            return Err(Error::from_response(&resp, "发送失败"));
         }
 
-        Ok(())
+        Ok(parse_sent_oid(&resp))
     }
 
     /// 当前话题
+    ///
+    /// 返回随在线列表推送缓存的话题，收到首次推送前为空
     pub async fn get_discuss(&self) -> String {
-        let discuss_guard = self.discuss.lock().await;
-        discuss_guard.clone()
+        self.live
+            .discuss
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
     }
 
     /// 设置当前话题
@@ -674,13 +1337,49 @@ impl ChatRoom {
 
     /// 当前在线人数
     pub async fn get_online_count(&self) -> usize {
-        let onlines_guard = self.onlines.lock().await;
-        onlines_guard.len()
+        self.live
+            .onlines
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .len()
+    }
+
+    /// 当前在线用户列表，为最近一次在线推送的副本
+    pub async fn get_onlines(&self) -> Vec<OnlineInfo> {
+        self.live
+            .onlines
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
     }
 
-    /// 重新设置apiKey
+    /// 重新设置apiKey，共享同一会话的模块一并生效
     pub fn set_api_key(&mut self, api_key: String) {
-        self.api_key = api_key;
+        self.session.set_api_key(api_key);
+    }
+
+    /// 设置共享的登录会话，之后从该会话读取 API key
+    pub fn set_session(&mut self, session: Arc<Session>) {
+        self.session = session;
+    }
+
+    /// 判断消息是否为当前用户发送
+    ///
+    /// - `msg` 聊天室消息
+    pub async fn is_own_message<T>(&self, msg: &ChatRoomMsg<T>) -> Result<bool, Error> {
+        self.session.is_self(&msg.userName).await
+    }
+
+    /// 判断消息是否提及当前用户
+    ///
+    /// - `msg` 聊天室消息
+    pub async fn is_mentioned(&self, msg: &ChatRoomMsg) -> Result<bool, Error> {
+        let content = if msg.md.is_empty() {
+            msg.content.as_str().unwrap_or_default()
+        } else {
+            msg.md.as_str()
+        };
+        self.session.is_mentioned(content).await
     }
 
     /// 设置客户端类型
@@ -697,36 +1396,32 @@ impl ChatRoom {
     ///
     /// #参数
     /// `page` - 页码
-    /// `type_` - 内容类型 [ChatContentType]
+    /// `type_` - 内容类型 [ChatContentType]，决定 `content` 的格式，`md` 始终为 Markdown
     pub async fn history(
         &self,
         page: u32,
         type_: ChatContentType,
     ) -> Result<Vec<ChatRoomMsg>, Error> {
-        let resp = get(&build_http_path(
-            "chat-room/more",
-            &[
-                ("page", page.to_string()),
-                ("type", type_.as_str().to_string()),
-                ("apiKey", self.api_key.clone()),
-            ],
-        ))
+        let resp = get_with_timeout(
+            &build_http_path(
+                "chat-room/more",
+                &[
+                    ("page", page.to_string()),
+                    ("type", type_.as_str().to_string()),
+                    ("apiKey", self.session.api_key()),
+                ],
+            ),
+            LONG_TIMEOUT,
+        )
         .await?;
 
         if let Some(code) = resp["code"].as_i64()
             && code != 0
         {
-            return Err(Error::Api(
-                resp["msg"].as_str().unwrap_or("Api error").to_string(),
-            ));
+            return Err(Error::from_response(&resp, "Api error"));
         }
 
-        let messages: Vec<ChatRoomMsg> = resp["data"]
-            .as_array()
-            .ok_or_else(|| Error::Api("Data is not an array".to_string()))?
-            .iter()
-            .map(ChatRoomMsg::from_value)
-            .collect::<Result<Vec<_>, _>>()?;
+        let messages = parse_history(&resp["data"], &type_)?;
         Ok(messages)
     }
 
@@ -736,7 +1431,7 @@ impl ChatRoom {
     /// * `o_id` - 消息 Id
     /// * `mode` - 获取模式，context 上下文模式，after 之后模式 [ChatRoomMessageMode]
     /// * `size` - 获取消息数量，默认 25，最大 100
-    /// * `type_` - 获取消息类型，默认 HTML [ChatContentType]
+    /// * `type_` - 获取消息类型，默认 HTML [ChatContentType]，决定 `content` 的格式，`md` 始终为 Markdown
     /// * 返回 [ChatRoomMsg] 消息列表
     pub async fn get_msg_around(
         &self,
@@ -745,34 +1440,26 @@ impl ChatRoom {
         size: u32,
         type_: ChatContentType,
     ) -> Result<Vec<ChatRoomMsg>, Error> {
-        let resp = get(&build_http_path(
-            "chat-room/getMessage",
-            &[
-                ("oId", o_id.to_string()),
-                ("mode", mode.to_string()),
-                ("size", size.to_string()),
-                ("type", type_.as_str().to_string()),
-                ("apiKey", self.api_key.clone()),
-            ],
-        ))
-        .await?;
-
-        if let Some(code) = resp["code"].as_i64()
-            && code != 0
-        {
-            return Err(Error::Api(
-                resp["msg"].as_str().unwrap_or("Api error").to_string(),
-            ));
-        }
+        fetch_msg_around(&self.session.api_key(), o_id, mode, size, &type_).await
+    }
 
-        let messages: Vec<ChatRoomMsg> = resp["data"]
-            .as_array()
-            .ok_or_else(|| Error::Api("Data is not an array".to_string()))?
-            .iter()
-            .map(ChatRoomMsg::from_value)
-            .collect::<Result<Vec<_>, _>>()?;
+    /// 获取指定消息之后的消息
+    ///
+    /// # 参数
+    /// * `o_id` - 消息 Id
+    /// * `type_` - 内容类型 [ChatContentType]
+    /// * 返回按时间升序排列的 [ChatRoomMsg] 消息列表，不包含 `o_id` 本身，最多 100 条
+    pub async fn history_since(
+        &self,
+        o_id: &str,
+        type_: ChatContentType,
+    ) -> Result<Vec<ChatRoomMsg>, Error> {
+        fetch_messages_after(&self.session.api_key(), o_id, &type_).await
+    }
 
-        Ok(messages)
+    /// 最近收到的消息 oId，断线重连后从这条消息之后补发
+    pub fn last_oid(&self) -> Option<String> {
+        self.live.last_oid()
     }
 
     /// 撤回消息
@@ -782,16 +1469,14 @@ impl ChatRoom {
     /// #### 返回 [RevokeMsg]
     pub async fn revoke(&self, o_id: &str) -> Result<RevokeMsg, Error> {
         let data = json!({
-            "apiKey": self.api_key,
+            "apiKey": self.session.api_key(),
         });
         let resp = delete(&format!("chat-room/revoke/{}", o_id), Some(data)).await?;
 
         if let Some(code) = resp["code"].as_i64()
             && code != 0
         {
-            return Err(Error::Api(
-                resp["msg"].as_str().unwrap_or("Api error").to_string(),
-            ));
+            return Err(Error::from_response(&resp, "Api error"));
         }
 
         Ok(RevokeMsg {
@@ -803,22 +1488,60 @@ impl ChatRoom {
     ///
     /// 再次发送相同 value 表示取消；发送不同 value 表示切换。
     pub async fn reaction(&self, o_id: &str, value: &str) -> Result<ReactionMutationResult, Error> {
-        crate::api::reaction::Reaction::new(self.api_key.clone())
+        crate::api::reaction::Reaction::new(self.session.api_key())
             .chat_room(o_id, value)
             .await
     }
 
+    /// 聊天室打卡：领取昨日活跃奖励并查询当前活跃度
+    ///
+    /// 摸鱼派根据在聊天室等处的活跃时长（活跃度）发放每日奖励，奖励在次日领取。
+    /// 已领取过时不会重复请求领取接口，[`LivenessReward::already_claimed`] 为 `true`。
+    /// 活跃度接口请求频率请至少 10 分钟一次。
+    ///
+    /// 返回领取结果 [LivenessReward]
+    pub async fn checkin(&self) -> Result<LivenessReward, Error> {
+        let already_claimed = fetch_liveness_collected(&self.session.api_key()).await?;
+        let points = if already_claimed {
+            0
+        } else {
+            claim_liveness_reward(&self.session.api_key()).await?
+        };
+        let liveness = fetch_liveness(&self.session.api_key()).await?;
+
+        Ok(LivenessReward {
+            points,
+            liveness,
+            already_claimed,
+        })
+    }
+
+    /// 获取当前用户的表情包，用于渲染表情选择器
+    ///
+    /// 返回表情包列表 [EmojiPack]，包含每个分组的名称及其中表情的图片 URL
+    pub async fn emoji_packs(&self) -> Result<Vec<EmojiPack>, Error> {
+        crate::api::emoji::Emoji::new(self.session.api_key())
+            .packs()
+            .await
+    }
+
     /// 发送弹幕
     ///
     /// #### 参数
     /// * `msg` - 弹幕内容
     /// * `color` - 颜色（可选）
-    pub async fn barrager(&self, msg: String, color: Option<String>) -> Result<String, Error> {
+    ///
+    /// 返回新弹幕消息的 oId，响应中没有 oId 时返回 `None`
+    pub async fn barrager(
+        &self,
+        msg: String,
+        color: Option<String>,
+    ) -> Result<Option<String>, Error> {
         let color = color.unwrap_or("#ffffff".to_string());
 
         let data = json!({
             "content": format!("[barrager]{{\"color\":\"{}\",\"content\":\"{}\"}}[/barrager]",color, msg),
-            "apiKey": self.api_key,
+            "apiKey": self.session.api_key(),
         });
 
         let resp = post("chat-room/send", Some(data)).await?;
@@ -826,12 +1549,10 @@ impl ChatRoom {
         if let Some(code) = resp["code"].as_i64()
             && code != 0
         {
-            return Err(Error::Api(
-                resp["msg"].as_str().unwrap_or("弹幕发送失败").to_string(),
-            ));
+            return Err(Error::from_response(&resp, "弹幕发送失败"));
         }
 
-        Ok(resp["msg"].as_str().unwrap_or("弹幕发送成功").to_string())
+        Ok(parse_sent_oid(&resp))
     }
 
     /// 获取弹幕花费
@@ -839,19 +1560,14 @@ impl ChatRoom {
     pub async fn barrage_cost(&self) -> Result<BarragerCost, Error> {
         let resp = get(&build_http_path(
             "chat-room/barrager/get",
-            &[("apiKey", self.api_key.clone())],
+            &[("apiKey", self.session.api_key())],
         ))
         .await?;
 
         if let Some(code) = resp["code"].as_i64()
             && code != 0
         {
-            return Err(Error::Api(
-                resp["msg"]
-                    .as_str()
-                    .unwrap_or("获取弹幕花费失败")
-                    .to_string(),
-            ));
+            return Err(Error::from_response(&resp, "获取弹幕花费失败"));
         }
 
         Ok(BarragerCost::from_value(&resp["data"]))
@@ -866,17 +1582,10 @@ impl ChatRoom {
         if let Some(code) = resp["code"].as_i64()
             && code != 0
         {
-            return Err(Error::Api(
-                resp["msg"]
-                    .as_str()
-                    .unwrap_or("获取禁言成员列表失败")
-                    .to_string(),
-            ));
+            return Err(Error::from_response(&resp, "获取禁言成员列表失败"));
         }
 
-        let messages: Vec<MuteItem> = resp["data"]
-            .as_array()
-            .ok_or_else(|| Error::Api("Data is not an array".to_string()))?
+        let messages: Vec<MuteItem> = as_array_or_empty(&resp["data"])?
             .iter()
             .map(MuteItem::from_value)
             .collect::<Result<Vec<_>, _>>()?;
@@ -884,6 +1593,16 @@ impl ChatRoom {
         Ok(messages)
     }
 
+    /// 获取消息的分享链接
+    ///
+    /// #### 参数
+    /// * `oid` - 消息 ID
+    ///
+    /// 返回形如 `https://fishpi.cn/cr#chatroom{oId}` 的链接
+    pub fn message_url(&self, oid: &str) -> String {
+        format!("{}/cr#chatroom{}", base_url(), oid)
+    }
+
     /// 获取消息原文（比如 Markdown）
     ///
     /// #### 参数
@@ -891,16 +1610,88 @@ impl ChatRoom {
     pub async fn get_raw_message(&self, o_id: &str) -> Result<String, Error> {
         let resp = get_text(&format!("cr/raw/{}", o_id,)).await?;
 
-        let raw_message = resp.split("<!--").next().unwrap_or("").trim().to_string();
-
-        Ok(raw_message)
+        Ok(strip_raw_comment(&resp))
     }
 }
 
+/// 解析历史消息列表，并按请求的内容类型整理 `content` 与 `md`
+fn parse_history(data: &Value, type_: &ChatContentType) -> Result<Vec<ChatRoomMsg>, Error> {
+    as_array_or_empty(data)?
+        .iter()
+        .map(|item| {
+            let mut msg = ChatRoomMsg::from_value(item)?;
+            msg.apply_content_type(type_);
+            Ok(msg)
+        })
+        .collect()
+}
+
+/// 从发送成功的响应中读取新消息的 oId，兼容放在顶层或 `data` 中的情况
+///
+/// 消息已发送成功，部分服务端版本不返回 oId，此时返回 `None` 而不是报错
+fn parse_sent_oid(resp: &Value) -> Option<String> {
+    [&resp["oId"], &resp["data"]["oId"], &resp["data"]]
+        .into_iter()
+        .find_map(|v| match v {
+            Value::String(s) if !s.is_empty() => Some(s.clone()),
+            Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        })
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{ChatRoomEventData, ChatRoomEventType, parse_chatroom_message};
-    use serde_json::json;
+    use super::{
+        ChatRoomEventData, ChatRoomEventType, LiveState, SeenOids, mark_edits,
+        parse_chatroom_message, parse_history, parse_sent_oid, record_last_oid, sync_state,
+    };
+    use crate::model::chatroom::{ChatContentType, WeatherCode};
+    use crate::model::redpacket::RedPacketType;
+    use serde_json::{Value, json};
+    use std::sync::Mutex;
+
+    fn history_item(content: &str, md: Option<&str>) -> Value {
+        let mut item = json!({
+            "oId": "1",
+            "time": "2024-01-01 00:00:00",
+            "userOId": 1,
+            "userName": "alice",
+            "userNickname": "",
+            "userAvatarURL": "",
+            "content": content,
+        });
+        if let Some(md) = md {
+            item["md"] = json!(md);
+        }
+        item
+    }
+
+    #[test]
+    fn parse_history_html_keeps_html_content_and_md() {
+        let data = json!([history_item("<p><strong>hi</strong></p>", Some("**hi**"))]);
+        let msgs = parse_history(&data, &ChatContentType::Html).expect("should parse");
+        assert_eq!(msgs[0].content, json!("<p><strong>hi</strong></p>"));
+        assert_eq!(msgs[0].md, "**hi**");
+        assert_eq!(msgs[0].userOId, "1");
+    }
+
+    #[test]
+    fn parse_history_markdown_fills_md_from_content() {
+        let data = json!([history_item("**hi**", None)]);
+        let msgs = parse_history(&data, &ChatContentType::Markdown).expect("should parse");
+        assert_eq!(msgs[0].content, json!("**hi**"));
+        assert_eq!(msgs[0].md, "**hi**");
+    }
+
+    #[test]
+    fn parse_sent_oid_from_response() {
+        let oid = parse_sent_oid(&json!({ "code": 0, "msg": "", "oId": "1700000000000" }));
+        assert_eq!(oid.as_deref(), Some("1700000000000"));
+        let oid = parse_sent_oid(&json!({ "code": 0, "data": { "oId": 1700000000001_i64 } }));
+        assert_eq!(oid.as_deref(), Some("1700000000001"));
+        // 发送成功但没有 oId 不是错误
+        assert_eq!(parse_sent_oid(&json!({ "code": 0, "msg": "" })), None);
+    }
 
     #[test]
     fn parse_chatroom_custom_message() {
@@ -939,10 +1730,223 @@ mod tests {
     }
 
     #[test]
-    fn parse_chatroom_unknown_type_fails() {
+    fn parse_chatroom_music_message() {
+        let mut payload = history_item(
+            r#"{"msgType":"music","type":"music","title":"晴天","source":"https://x/a.mp3","coverURL":"https://x/c.jpg","from":"网易云"}"#,
+            None,
+        );
+        payload["type"] = json!("msg");
+
+        let (event_type, event) = parse_chatroom_message(&payload).expect("should parse");
+        assert_eq!(event_type, ChatRoomEventType::Music);
+        match event {
+            ChatRoomEventData::Music(msg) => {
+                assert_eq!(msg.content.title, "晴天");
+                assert_eq!(msg.content.cover, "https://x/c.jpg");
+                assert_eq!(msg.content.raw["type"], "music");
+            }
+            _ => panic!("unexpected event variant"),
+        }
+    }
+
+    #[test]
+    fn parse_chatroom_weather_message() {
+        let mut payload = history_item(
+            r#"{"msgType":"weather","type":"weather","t":"上海","st":"晴转多云","date":"9/16,9/17","weatherCode":"CLEAR_DAY,SOMETHING_NEW","min":"20,21","max":"30,29"}"#,
+            None,
+        );
+        payload["type"] = json!("msg");
+
+        let (event_type, event) = parse_chatroom_message(&payload).expect("should parse");
+        assert_eq!(event_type, ChatRoomEventType::Weather);
+        match event {
+            ChatRoomEventData::Weather(msg) => {
+                assert_eq!(msg.content.city, "上海");
+                assert_eq!(msg.content.data.len(), 2);
+                assert_eq!(msg.content.data[0].code, WeatherCode::ClearDay);
+                assert_eq!(msg.content.data[1].code, WeatherCode::Unknown);
+                assert_eq!(msg.content.data[1].max, "29");
+            }
+            _ => panic!("unexpected event variant"),
+        }
+    }
+
+    #[test]
+    fn parse_chatroom_redpacket_message() {
+        let mut payload = history_item(
+            r#"{"msgType":"redPacket","type":"specify","msg":"给你","money":64,"count":1,"got":0,"who":[],"senderId":"1","recivers":"[\"bob\"]"}"#,
+            None,
+        );
+        payload["type"] = json!("msg");
+
+        let (event_type, event) = parse_chatroom_message(&payload).expect("should parse");
+        assert_eq!(event_type, ChatRoomEventType::RedPacket);
+        match event {
+            ChatRoomEventData::RedPacket(msg) => {
+                assert_eq!(msg.content.money, 64);
+                assert!(matches!(msg.content.r#type, Some(RedPacketType::Specify)));
+                assert_eq!(msg.content.recivers, vec!["bob".to_string()]);
+            }
+            _ => panic!("unexpected event variant"),
+        }
+    }
+
+    #[test]
+    fn repeated_oid_is_marked_as_edit() {
+        let seen = Mutex::new(SeenOids::default());
+        let payload = json!({
+            "type": "msg",
+            "oId": "42",
+            "time": "2024-01-01 00:00:00",
+            "userOId": 1,
+            "userName": "alice",
+            "userNickname": "",
+            "userAvatarURL": "",
+            "content": "hello",
+        });
+
+        let (event_type, event) = parse_chatroom_message(&payload).expect("should parse");
+        let (first_type, _) =
+            mark_edits(&seen, event_type.clone(), event.clone()).expect("first delivery");
+        assert_eq!(first_type, ChatRoomEventType::Msg);
+
+        let (second_type, second) =
+            mark_edits(&seen, event_type.clone(), event.clone()).expect("edit is delivered");
+        assert_eq!(second_type, ChatRoomEventType::Edit);
+        assert!(matches!(second, ChatRoomEventData::Edit(msg) if msg.oId == "42"));
+
+        // 补发过的消息，实时推送的副本被丢弃，之后再推送才视为编辑
+        let seen = Mutex::new(SeenOids::default());
+        assert!(seen.lock().unwrap().insert_replayed("42"));
+        assert!(!seen.lock().unwrap().insert_replayed("42"));
+        assert!(mark_edits(&seen, event_type.clone(), event.clone()).is_none());
+        let (third_type, _) = mark_edits(&seen, event_type, event).expect("edit is delivered");
+        assert_eq!(third_type, ChatRoomEventType::Edit);
+    }
+
+    #[test]
+    fn online_discussing_updates_cache_and_emits_discuss_changed() {
+        let discuss = Mutex::new(String::new());
+        let onlines = Mutex::new(Vec::new());
+        let payload = json!({
+            "type": "online",
+            "users": [],
+            "discussing": "摸鱼",
+            "onlineChatCnt": 3
+        });
+
+        let (event_type, event) = parse_chatroom_message(&payload).expect("should parse");
+        let events = sync_state(&discuss, &onlines, event_type.clone(), event.clone());
+        assert_eq!(events.len(), 2);
+        assert!(
+            matches!(&events[1], (ChatRoomEventType::DiscussChanged, ChatRoomEventData::DiscussChanged(t)) if t == "摸鱼")
+        );
+        assert_eq!(*discuss.lock().unwrap(), "摸鱼");
+
+        let events = sync_state(&discuss, &onlines, event_type, event);
+        assert_eq!(events.len(), 1);
+    }
+
+    #[test]
+    fn online_update_emits_joined_and_left() {
+        let discuss = Mutex::new(String::new());
+        let onlines = Mutex::new(Vec::new());
+        let online = |names: &[&str]| {
+            let users: Vec<Value> = names
+                .iter()
+                .map(|name| json!({ "homePage": "", "userAvatarURL": "", "userName": name }))
+                .collect();
+            parse_chatroom_message(&json!({ "type": "online", "users": users }))
+                .expect("should parse")
+        };
+
+        let (event_type, event) = online(&["alice", "bob"]);
+        assert_eq!(sync_state(&discuss, &onlines, event_type, event).len(), 1);
+
+        let (event_type, event) = online(&["bob", "carol"]);
+        let events = sync_state(&discuss, &onlines, event_type, event);
+        assert_eq!(events.len(), 2);
+        match &events[1] {
+            (
+                ChatRoomEventType::OnlineChanged,
+                ChatRoomEventData::OnlineChanged { joined, left },
+            ) => {
+                assert_eq!(joined[0].userName, "carol");
+                assert_eq!(left[0].userName, "alice");
+            }
+            _ => panic!("unexpected event variant"),
+        }
+        assert_eq!(onlines.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn parse_chatroom_unknown_type_is_observable() {
         let payload = json!({
             "type": "nope"
         });
-        assert!(parse_chatroom_message(&payload).is_err());
+        let (event_type, event) = parse_chatroom_message(&payload).expect("should parse");
+        assert_eq!(event_type, ChatRoomEventType::Unknown);
+        match event {
+            ChatRoomEventData::Unknown { type_, raw } => {
+                assert_eq!(type_, "nope");
+                assert_eq!(raw, payload);
+            }
+            _ => panic!("unexpected event variant"),
+        }
+    }
+
+    #[test]
+    fn parse_chatroom_missing_type_fails() {
+        assert!(parse_chatroom_message(&json!({})).is_err());
+    }
+
+    #[test]
+    fn last_oid_tracks_newest_message() {
+        let last_oid = Mutex::new(None);
+        let msg = |oid: &str| {
+            let mut item = history_item("hi", None);
+            item["oId"] = json!(oid);
+            parse_history(&json!([item]), &ChatContentType::Html).expect("should parse")[0].clone()
+        };
+
+        record_last_oid(&last_oid, &ChatRoomEventData::Msg(msg("1700000000002")));
+        record_last_oid(&last_oid, &ChatRoomEventData::Msg(msg("1700000000001")));
+        record_last_oid(
+            &last_oid,
+            &ChatRoomEventData::Revoke("1700000000009".into()),
+        );
+
+        assert_eq!(last_oid.lock().unwrap().as_deref(), Some("1700000000002"));
+    }
+
+    #[test]
+    fn live_events_are_held_during_replay() {
+        let live = LiveState::default();
+        let msg = |oid: &str| {
+            let mut item = history_item("hi", None);
+            item["oId"] = json!(oid);
+            parse_history(&json!([item]), &ChatContentType::Html).expect("should parse")[0].clone()
+        };
+        live.transform(
+            ChatRoomEventType::Msg,
+            ChatRoomEventData::Msg(msg("1700000000001")),
+        );
+
+        live.hold();
+        let events = live.transform(
+            ChatRoomEventType::Msg,
+            ChatRoomEventData::Msg(msg("1700000000005")),
+        );
+        assert!(events.is_empty());
+        assert_eq!(live.last_oid().as_deref(), Some("1700000000001"));
+
+        let held = live.take_held().expect("held event");
+        assert_eq!(held.len(), 1);
+        assert!(live.take_held().is_none());
+        let events = live.transform(
+            ChatRoomEventType::Msg,
+            ChatRoomEventData::Msg(msg("1700000000006")),
+        );
+        assert_eq!(events.len(), 1);
     }
 }
@@ -10,10 +10,13 @@
 //! # 方法列表
 //!
 //! - [`Comment::new`] - 创建新的评论客户端实例。
+//! - [`Comment::with_session`] - 使用共享的登录会话创建实例。
+//! - [`Comment::list`] - 分页获取文章评论。
+//! - [`Comment::raw_content`] - 获取评论的 Markdown 原文。
 //! - [`Comment::send`] - 发布评论。
 //! - [`Comment::update`] - 更新评论。
 //! - [`Comment::vote`] - 评论点赞。
-//! - [`Comment::thank`] - 评论感谢。
+//! - [`Comment::thank`] - 评论感谢，返回最新感谢数。
 //! - [`Comment::remove`] - 删除评论。
 //!
 //! # 示例
@@ -43,26 +46,55 @@
 //!
 //!     // 点赞评论
 //!     let voted = comment.vote("comment_id", true).await?;
-//!     println!("Voted: {}", voted);
+//!     println!("Voted: {:?}", voted);
 //!
 //!     Ok(())
 //! }
 //! ```
-use serde_json::{Value, json};
+use serde::Serialize;
+use std::sync::Arc;
 
 use crate::{
-    model::article::CommentPost,
+    api::session::Session,
+    model::article::{CommentPage, CommentPost, ThankResult, VoteStatus},
     model::reaction::ReactionMutationResult,
-    utils::{ResponseResult, error::Error, post, put},
+    utils::{
+        EmptyBody, ResponseResult, build_http_path, error::Error, get, get_text, post_with_key,
+        put_with_key, strip_raw_comment,
+    },
 };
 
+/// 评论投票请求体
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VoteBody<'a> {
+    data_id: &'a str,
+}
+
+/// 评论感谢请求体
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ThankBody<'a> {
+    comment_id: &'a str,
+}
+
 pub struct Comment {
-    api_key: String,
+    session: Arc<Session>,
 }
 
 impl Comment {
     pub fn new(api_key: String) -> Self {
-        Self { api_key }
+        Self::with_session(Session::new(api_key))
+    }
+
+    /// 使用共享的登录会话创建实例，API key 从会话读取
+    pub fn with_session(session: Arc<Session>) -> Self {
+        Self { session }
+    }
+
+    /// 重新设置 API key，共享同一会话的模块一并生效
+    pub fn set_api_key(&mut self, api_key: String) {
+        self.session.set_api_key(api_key);
     }
 
     /// 给评论添加/切换/取消 emoji reaction。
@@ -73,11 +105,47 @@ impl Comment {
         comment_id: &str,
         value: &str,
     ) -> Result<ReactionMutationResult, Error> {
-        crate::api::reaction::Reaction::new(self.api_key.clone())
+        crate::api::reaction::Reaction::new(self.session.api_key())
             .comment(comment_id, value)
             .await
     }
 
+    /// 分页获取文章评论
+    ///
+    /// 服务端没有单独的评论列表接口，评论只能随文章详情接口（`api/article/{id}`）返回，
+    /// 因此每次请求仍会带回整篇文章。该接口只接受评论页码：每页数量固定为服务端设置，
+    /// 排序跟随登录用户在网页端设置的评论浏览模式，无法按请求指定数量或升降序。
+    ///
+    /// - `article_id` 文章 Id
+    /// - `page` 页码
+    ///
+    /// 返回一页评论及分页信息 [CommentPage]
+    pub async fn list(&self, article_id: &str, page: u32) -> Result<CommentPage, Error> {
+        let url = build_http_path(
+            &format!("api/article/{}", article_id),
+            &[("p", page.to_string()), ("apiKey", self.session.api_key())],
+        );
+
+        let rsp = get(&url).await?;
+
+        if rsp.get("code").and_then(|c| c.as_i64()).unwrap_or(-1) != 0 {
+            return Err(Error::from_response(&rsp, "API error"));
+        }
+
+        CommentPage::from_value(&rsp["data"], page)
+    }
+
+    /// 获取评论的 Markdown 原文，可用于编辑时填充内容
+    ///
+    /// - `id` 评论 Id
+    ///
+    /// 返回 Markdown 原文
+    pub async fn raw_content(&self, id: &str) -> Result<String, Error> {
+        let resp = get_text(&format!("comment/{}/raw", id)).await?;
+
+        Ok(strip_raw_comment(&resp))
+    }
+
     /// 发布评论
     ///
     /// - `data` 评论信息
@@ -86,10 +154,7 @@ impl Comment {
     pub async fn send(&self, data: &CommentPost) -> Result<ResponseResult, Error> {
         let url = "comment".to_string();
 
-        let mut data_json = data.to_value()?;
-        data_json["apiKey"] = Value::String(self.api_key.clone());
-
-        let rsp = post(&url, Some(data_json)).await?;
+        let rsp = post_with_key(&url, data, &self.session.api_key()).await?;
 
         ResponseResult::from_value(&rsp)
     }
@@ -103,15 +168,10 @@ impl Comment {
     pub async fn update(&self, id: &str, data: &CommentPost) -> Result<String, Error> {
         let url = format!("comment/{}", id);
 
-        let mut data_json = data.to_value()?;
-        data_json["apiKey"] = Value::String(self.api_key.clone());
-
-        let rsp = put(&url, Some(data_json)).await?;
+        let rsp = put_with_key(&url, data, &self.session.api_key()).await?;
 
         if rsp.get("code").and_then(|c| c.as_i64()).unwrap_or(-1) != 0 {
-            return Err(Error::Api(
-                rsp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+            return Err(Error::from_response(&rsp, "API error"));
         }
 
         Ok(rsp["commentContent"].as_str().unwrap_or("").to_string())
@@ -122,43 +182,36 @@ impl Comment {
     /// - `id` 评论 Id
     /// - `like` 点赞类型，true 为点赞，false 为点踩
     ///
-    /// 返回评论点赞状态，true 为点赞，false 为点踩
-    pub async fn vote(&self, id: &str, like: bool) -> Result<bool, Error> {
+    /// 返回投票后的状态 [VoteStatus]，重复同一种投票会取消，返回 [VoteStatus::Normal]
+    pub async fn vote(&self, id: &str, like: bool) -> Result<VoteStatus, Error> {
         let action = if like { "up" } else { "down" };
         let url = format!("vote/{}/comment", action);
 
-        let data_json = json!({
-            "dataId": id,
-            "apiKey": self.api_key,
-        });
+        let data = VoteBody { data_id: id };
 
-        let rsp = post(&url, Some(data_json)).await?;
+        let rsp = post_with_key(&url, &data, &self.session.api_key()).await?;
 
         if rsp.get("code").and_then(|c| c.as_i64()).unwrap_or(-1) != 0 {
-            return Err(Error::Api(
-                rsp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+            return Err(Error::from_response(&rsp, "API error"));
         }
 
-        Ok(rsp["type"].as_i64().unwrap_or(-1) == 0)
+        let previous = rsp["type"].as_i64().unwrap_or(-1);
+        Ok(VoteStatus::after_vote(like, previous))
     }
 
     /// 评论感谢
     ///
     /// - `id` 评论 Id
     ///
-    /// 返回执行结果
-    pub async fn thank(&self, id: &str) -> Result<ResponseResult, Error> {
+    /// 返回感谢后的感谢数 [ThankResult]，已感谢过时返回 [`Error::AlreadyThanked`]
+    pub async fn thank(&self, id: &str) -> Result<ThankResult, Error> {
         let url = "comment/thank".to_string();
 
-        let data_json = json!({
-            "apiKey": self.api_key,
-            "commentId": id,
-        });
+        let data = ThankBody { comment_id: id };
 
-        let rsp = post(&url, Some(data_json)).await?;
+        let rsp = post_with_key(&url, &data, &self.session.api_key()).await?;
 
-        ResponseResult::from_value(&rsp)
+        ThankResult::from_value(&rsp)
     }
 
     /// 删除评论
@@ -169,16 +222,10 @@ impl Comment {
     pub async fn remove(&self, id: &str) -> Result<String, Error> {
         let url = format!("comment/{}/remove", id);
 
-        let data_json = json!({
-            "apiKey": self.api_key,
-        });
-
-        let rsp = post(&url, Some(data_json)).await?;
+        let rsp = post_with_key(&url, &EmptyBody {}, &self.session.api_key()).await?;
 
         if rsp.get("code").and_then(|c| c.as_i64()).unwrap_or(-1) != 0 {
-            return Err(Error::Api(
-                rsp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+            return Err(Error::from_response(&rsp, "API error"));
         }
 
         Ok(rsp["commentId"].as_str().unwrap_or("").to_string())
@@ -3,7 +3,7 @@
 //! 该模块负责管理自定义表情包分组和表情项。这里的“表情包”与
 //! [`crate::api::reaction`] 中的“给内容贴 emoji reaction”是两套不同能力。
 
-use crate::model::emoji::{EmojiGroup, EmojiItem};
+use crate::model::emoji::{EmojiGroup, EmojiItem, EmojiPack};
 use crate::utils::{build_http_path, error::Error, get_with_body, post};
 use serde_json::{Value, json};
 
@@ -34,6 +34,19 @@ impl Emoji {
         parse_items(&resp)
     }
 
+    /// 获取全部表情包，即每个分组及其中的表情。
+    ///
+    /// 会为每个分组单独请求一次表情列表。
+    pub async fn packs(&self) -> Result<Vec<EmojiPack>, Error> {
+        let mut packs = Vec::new();
+        for group in self.groups().await? {
+            let emojis = self.group_emojis(&group.oId).await?;
+            packs.push(EmojiPack::new(group, emojis));
+        }
+        packs.sort_by_key(|pack| pack.group.sort);
+        Ok(packs)
+    }
+
     /// 上传 URL 到“全部”分组。
     pub async fn upload_url(&self, url: &str) -> Result<EmojiItem, Error> {
         let resp = post(
@@ -201,12 +214,7 @@ fn ensure_success(resp: &Value, fallback: &str) -> Result<(), Error> {
     if let Some(code) = resp.get("code").and_then(|c| c.as_i64())
         && code != 0
     {
-        return Err(Error::Api(
-            resp.get("msg")
-                .and_then(|m| m.as_str())
-                .unwrap_or(fallback)
-                .to_string(),
-        ));
+        return Err(Error::from_response(resp, fallback));
     }
 
     Ok(())
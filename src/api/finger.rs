@@ -1,13 +1,20 @@
+use futures_util::{StreamExt, stream};
 use serde_json::{Value, json};
 
 use crate::{
     model::{
-        finger::{UserBag, UserBagType, UserIP},
-        user::MetalBase,
+        finger::{ModerationProfile, UserBag, UserBagType, UserIP},
+        user::{MetalBase, UserPoint},
     },
-    utils::{ResponseResult, error::Error, post},
+    utils::{ResponseResult, error::Error, get, post},
 };
 
+/// 批量操作的最大并发请求数
+pub const BATCH_CONCURRENCY: usize = 4;
+
+/// 金手指管理客户端，通过 [`crate::FishPi::finger`] 创建
+///
+/// 所有请求使用金手指 key（`goldFingerKey`）认证，而不是用户 API key。
 pub struct Finger {
     key: String,
 }
@@ -17,6 +24,24 @@ impl Finger {
         Self { key }
     }
 
+    /// 校验金手指 key 是否有效
+    ///
+    /// 使用空用户名查询背包作为探测请求，不会修改任何数据。
+    ///
+    /// 返回 key 是否有效
+    pub async fn validate(&self) -> Result<bool, Error> {
+        let data = json!({
+            "goldFingerKey": self.key,
+            "userName": "",
+        });
+
+        match finger_post("user/query/items", data).await {
+            Ok(_) => Ok(true),
+            Err(Error::InvalidFingerKey(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
     /// 上传摸鱼大闯关关卡数据
     ///
     /// - `user_name` 用户在摸鱼派的用户名
@@ -46,7 +71,7 @@ impl Finger {
             "time": time,
         });
 
-        let rsp = post(&url, Some(data)).await?;
+        let rsp = finger_post(&url, data).await?;
 
         ResponseResult::from_value(&rsp)
     }
@@ -64,17 +89,65 @@ impl Finger {
             "userName": user_name,
         });
 
-        let rsp = post(&url, Some(data)).await?;
+        let rsp = finger_post(&url, data).await?;
 
         if rsp.get("code").and_then(|c| c.as_i64()).unwrap_or(-1) != 0 {
-            return Err(Error::Api(
-                rsp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+            return Err(Error::from_response(&rsp, "API error"));
         }
 
         UserIP::from_value(&rsp)
     }
 
+    /// 查询用户管理概览
+    ///
+    /// 并发查询最近登录 IP、积分、活跃度和背包，单项失败不影响其他项目，
+    /// 失败原因记录在 [`ModerationProfile::errors`] 中。
+    ///
+    /// - `user_name` 用户在摸鱼派的用户名
+    ///
+    /// 返回用户管理概览
+    pub async fn query_user(&self, user_name: &str) -> Result<ModerationProfile, Error> {
+        let (ip, points, liveness, bag) = tokio::join!(
+            self.query_latest_login_ip(user_name),
+            Self::query_points(user_name),
+            self.get_liveness(user_name),
+            self.query_user_bag(user_name),
+        );
+
+        let mut errors = Vec::new();
+        let mut record = |item: &str, e: Error| errors.push((item.to_string(), e.to_string()));
+
+        let ip = ip.map_err(|e| record("ip", e)).ok();
+        let points = points
+            .map(|p| p.point)
+            .map_err(|e| record("points", e))
+            .ok();
+        let liveness = liveness.map_err(|e| record("liveness", e)).ok();
+        let bag = bag.map_err(|e| record("bag", e)).ok();
+
+        let profile = ModerationProfile {
+            user_name: user_name.to_string(),
+            ip,
+            points,
+            liveness,
+            bag,
+            errors,
+        };
+
+        Ok(profile)
+    }
+
+    /// 查询用户积分
+    async fn query_points(user_name: &str) -> Result<UserPoint, Error> {
+        let rsp = get(&format!("user/{}/point", user_name)).await?;
+
+        if rsp.get("code").and_then(|c| c.as_i64()).unwrap_or(-1) != 0 {
+            return Err(Error::from_response(&rsp, "API error"));
+        }
+
+        UserPoint::from_value(&rsp)
+    }
+
     /// 添加勋章
     ///
     /// - `user_name` 用户在摸鱼派的用户名
@@ -94,11 +167,47 @@ impl Finger {
         data["userName"] = Value::String(user_name.to_string());
         data["attr"] = Value::String(metal.attr.to_string());
 
-        let rsp = post(&url, Some(data)).await?;
+        let rsp = finger_post(&url, data).await?;
 
         ResponseResult::from_value(&rsp)
     }
 
+    /// 批量添加勋章
+    ///
+    /// 并发请求，同时最多 [`BATCH_CONCURRENCY`] 个；单个用户请求失败时，
+    /// 错误信息记录在对应的执行结果中，不影响其他用户。
+    ///
+    /// - `user_names` 用户在摸鱼派的用户名列表
+    /// - `metal` 勋章信息
+    ///
+    /// 返回 (用户名, 执行结果) 列表，顺序与 `user_names` 一致
+    pub async fn add_metal_batch(
+        &self,
+        user_names: &[&str],
+        metal: &MetalBase,
+    ) -> Result<Vec<(String, ResponseResult)>, Error> {
+        // 提前检查勋章能否序列化，避免每个用户都得到相同的错误
+        serde_json::to_value(metal)
+            .map_err(|e| Error::Parse(format!("Failed to serialize MetalBase: {}", e)))?;
+
+        let results = stream::iter(user_names.iter().copied())
+            .map(|user_name| async move {
+                let result =
+                    self.add_metal(user_name, metal)
+                        .await
+                        .unwrap_or_else(|e| ResponseResult {
+                            success: false,
+                            msg: e.to_string(),
+                        });
+                (user_name.to_string(), result)
+            })
+            .buffered(BATCH_CONCURRENCY)
+            .collect()
+            .await;
+
+        Ok(results)
+    }
+
     /// 删除勋章
     ///
     /// - `user_name` 用户在摸鱼派的用户名
@@ -114,7 +223,7 @@ impl Finger {
             "name": name,
         });
 
-        let rsp = post(&url, Some(data)).await?;
+        let rsp = finger_post(&url, data).await?;
 
         ResponseResult::from_value(&rsp)
     }
@@ -138,7 +247,7 @@ impl Finger {
             "name": name,
         });
 
-        let rsp = post(&url, Some(data)).await?;
+        let rsp = finger_post(&url, data).await?;
 
         ResponseResult::from_value(&rsp)
     }
@@ -156,12 +265,10 @@ impl Finger {
             "userName": user_name,
         });
 
-        let rsp = post(&url, Some(data_json)).await?;
+        let rsp = finger_post(&url, data_json).await?;
 
         if rsp.get("code").and_then(|c| c.as_i64()).unwrap_or(-1) != 0 {
-            return Err(Error::Api(
-                rsp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+            return Err(Error::from_response(&rsp, "API error"));
         }
 
         UserBag::from_value(&rsp)
@@ -189,7 +296,7 @@ impl Finger {
             "sum": sum,
         });
 
-        let rsp = post(&url, Some(data_json)).await?;
+        let rsp = finger_post(&url, data_json).await?;
 
         ResponseResult::from_value(&rsp)
     }
@@ -216,11 +323,42 @@ impl Finger {
             "memo": memo,
         });
 
-        let rsp = post(&url, Some(data_json)).await?;
+        let rsp = finger_post(&url, data_json).await?;
 
         ResponseResult::from_value(&rsp)
     }
 
+    /// 批量调整用户积分
+    ///
+    /// 并发请求，同时最多 [`BATCH_CONCURRENCY`] 个。**本操作不是原子的**：
+    /// 部分用户可能调整成功而其他用户失败，请根据返回的执行结果逐条核对，
+    /// 重试时只提交失败的条目，避免重复发放。
+    ///
+    /// - `entries` (用户名, 积分数量, 备注) 列表
+    ///
+    /// 返回 (用户名, 执行结果) 列表，顺序与 `entries` 一致
+    pub async fn edit_points_batch(
+        &self,
+        entries: &[(String, i32, String)],
+    ) -> Result<Vec<(String, ResponseResult)>, Error> {
+        let results = stream::iter(entries)
+            .map(|(user_name, point, memo)| async move {
+                let result = self
+                    .edit_user_points(user_name, *point, memo)
+                    .await
+                    .unwrap_or_else(|e| ResponseResult {
+                        success: false,
+                        msg: e.to_string(),
+                    });
+                (user_name.clone(), result)
+            })
+            .buffered(BATCH_CONCURRENCY)
+            .collect()
+            .await;
+
+        Ok(results)
+    }
+
     /// 查询用户当前活跃度
     ///
     /// - `user_name` 用户在摸鱼派的用户名
@@ -234,12 +372,10 @@ impl Finger {
             "userName": user_name,
         });
 
-        let rsp = post(&url, Some(data_json)).await?;
+        let rsp = finger_post(&url, data_json).await?;
 
         if rsp.get("code").and_then(|c| c.as_i64()).unwrap_or(-1) != 0 {
-            return Err(Error::Api(
-                rsp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+            return Err(Error::from_response(&rsp, "API error"));
         }
 
         Ok(rsp["liveness"].as_f64().unwrap_or(0.0))
@@ -258,14 +394,43 @@ impl Finger {
             "userName": user_name,
         });
 
-        let rsp = post(&url, Some(data_json)).await?;
+        let rsp = finger_post(&url, data_json).await?;
 
         if rsp.get("code").and_then(|c| c.as_i64()).unwrap_or(-1) != 0 {
-            return Err(Error::Api(
-                rsp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+            return Err(Error::from_response(&rsp, "API error"));
         }
 
         Ok(rsp["sum"].as_f64().unwrap_or(0.0))
     }
 }
+
+/// 发送金手指请求，key 无效时返回 [Error::InvalidFingerKey]
+async fn finger_post(url: &str, data: Value) -> Result<Value, Error> {
+    let rsp = post(url, Some(data)).await?;
+
+    if rsp.get("code").and_then(|c| c.as_i64()).unwrap_or(0) != 0 {
+        let msg = rsp["msg"].as_str().unwrap_or("");
+        if is_invalid_key(msg) {
+            return Err(Error::InvalidFingerKey(msg.to_string()));
+        }
+    }
+
+    Ok(rsp)
+}
+
+/// 判断错误信息是否表示金手指 key 无效
+fn is_invalid_key(msg: &str) -> bool {
+    msg.contains("金手指") || msg.to_ascii_lowercase().contains("goldfingerkey")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_invalid_key;
+
+    #[test]
+    fn invalid_finger_key_message_is_detected() {
+        assert!(is_invalid_key("金手指不存在"));
+        assert!(is_invalid_key("Invalid goldFingerKey"));
+        assert!(!is_invalid_key("用户不存在"));
+    }
+}
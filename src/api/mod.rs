@@ -8,5 +8,6 @@ pub mod finger;
 pub mod notice;
 pub mod reaction;
 pub mod redpacket;
+pub mod session;
 pub mod user;
 pub mod ws;
@@ -10,19 +10,27 @@
 //! - [`NoticeHandler`] - 通知消息处理器，实现 `MessageHandler` trait，处理 WebSocket 消息并发射事件。
 //! - [`NoticeEventData`] - 通知事件数据枚举，包装通知消息。
 //! - [`NoticeListener`] - 通知事件监听器类型别名，定义监听器函数的签名。
+//! - [`NoticeSubscription`] - 通知事件流，丢弃时自动移除对应的监听器。
 //!
 //! # 方法列表
 //!
 //! - [`Notice::new`] - 创建新的通知客户端实例。
+//! - [`Notice::with_session`] - 使用共享的登录会话创建实例。
 //! - [`Notice::connect`] - 连接通知 WebSocket。
 //! - [`Notice::reconnect`] - 重连通知 WebSocket。
+//! - [`Notice::is_connected`] - 是否已连接。
+//! - [`Notice::on_reconnecting`] - 监听重连状态，每次重连前调用。
+//! - [`Notice::set_api_key`] - 重新设置 API key。
 //! - [`Notice::on_notice`] - 监听通知消息事件。
 //! - [`Notice::off`] - 移除事件监听器。
 //! - [`Notice::disconnect`] - 断开连接。
 //! - [`Notice::count`] - 获取未读消息数。
 //! - [`Notice::list`] - 获取消息列表。
+//! - [`Notice::list_page`] - 分页获取消息列表。
 //! - [`Notice::make_read`] - 已读指定类型消息。
 //! - [`Notice::read_all`] - 已读所有消息。
+//! - [`Notice::all_unread`] - 获取所有类型的未读通知。
+//! - [`Notice::subscribe_with_backfill`] - 订阅通知流，先推送当前未读通知再推送实时消息。
 //!
 //! # 示例
 //!
@@ -55,25 +63,34 @@
 //! 通知支持以下事件类型（通过特定 `on_*` 方法监听）：
 //!
 //! - `Msg` - 通知消息接收。
+//! - `Item` - 通知列表项，由 [`Notice::subscribe_with_backfill`] 回填未读通知时产生。
 
+use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
 
+use futures_util::{Stream, StreamExt, stream};
 use serde_json::Value;
+use tokio::sync::mpsc;
 
 use crate::{
+    api::session::Session,
     api::ws::{
-        ParsedMessageHandler, RetryPolicy, WebSocketError, WsConnection, WsLogHook, build_ws_url,
+        EventBus, ParsedMessageHandler, RetryPolicy, TypedListener, WebSocketError, WsConnection,
+        WsLogHook, build_ws_url,
     },
-    model::notice::{NoticeCount, NoticeItem, NoticeList, NoticeMsg, NoticeMsgType, NoticeType},
-    utils::{build_http_path, error::Error, get},
+    model::notice::{
+        NoticeCount, NoticeItem, NoticeList, NoticeMsg, NoticeMsgType, NoticePage, NoticeType,
+    },
+    utils::{as_array_or_empty, build_http_path, error::Error, get, ws_domain},
 };
 
-const DOMAIN: &str = "fishpi.cn";
-
 /// 通知项联合类型
 #[derive(Clone, Debug)]
 pub enum NoticeEventData {
     Msg(NoticeMsg),
+    Item(NoticeItem),
 }
 
 /// 通知事件类型枚举
@@ -87,6 +104,37 @@ pub type NoticeListener = Arc<dyn Fn(NoticeEventData) + Send + Sync + 'static>;
 /// 消息处理器
 pub type NoticeHandler = ParsedMessageHandler<NoticeEventType, NoticeEventData>;
 
+/// 通知事件流，由 [`Notice::subscribe_with_backfill`] 返回
+///
+/// 丢弃时自动移除订阅时注册的监听器，不影响通过 [`Notice::on_notice`] 注册的其他监听器。
+pub struct NoticeSubscription {
+    inner: Pin<Box<dyn Stream<Item = NoticeEventData> + Send>>,
+    emitter: EventBus<NoticeEventType, NoticeEventData>,
+    listener: TypedListener<NoticeEventData>,
+}
+
+impl Stream for NoticeSubscription {
+    type Item = NoticeEventData;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+impl Drop for NoticeSubscription {
+    fn drop(&mut self) {
+        let emitter = self.emitter.clone();
+        let listener = self.listener.clone();
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            handle.spawn(async move {
+                emitter
+                    .remove_listener_handle(&NoticeEventType::Msg, &listener)
+                    .await;
+            });
+        }
+    }
+}
+
 /// 解析通知消息，返回(事件类型，事件数据)
 #[allow(non_snake_case)]
 fn parse_notice_message(data: &Value) -> Result<(NoticeEventType, NoticeEventData), Error> {
@@ -116,20 +164,29 @@ fn parse_notice_message(data: &Value) -> Result<(NoticeEventType, NoticeEventDat
 pub struct Notice {
     connection: WsConnection,
     handler: NoticeHandler,
-    api_key: String,
+    session: Arc<Session>,
 }
 
 impl Notice {
     pub fn new(api_key: String) -> Self {
+        Self::with_session(Session::new(api_key))
+    }
+
+    /// 使用共享的登录会话创建实例，API key 从会话读取
+    pub fn with_session(session: Arc<Session>) -> Self {
         Self {
             connection: WsConnection::new(),
             handler: NoticeHandler::new(parse_notice_message, None, "notice"),
-            api_key,
+            session,
         }
     }
 
     fn ws_url(&self) -> Result<String, WebSocketError> {
-        build_ws_url(DOMAIN, "user-channel", &[("apiKey", self.api_key.clone())])
+        build_ws_url(
+            &ws_domain(),
+            "user-channel",
+            &[("apiKey", self.session.api_key())],
+        )
     }
 
     pub async fn connect(&mut self, reload: bool) -> Result<(), WebSocketError> {
@@ -145,10 +202,27 @@ impl Notice {
         self.connection.reconnect(&url, self.handler.clone()).await
     }
 
+    /// 是否已连接
+    pub fn is_connected(&self) -> bool {
+        self.connection.is_connected()
+    }
+
+    /// 重新设置 API key，保留已注册的监听器，共享同一会话的模块一并生效
+    ///
+    /// 已建立的连接仍使用旧 key，需调用 [`Notice::reconnect`] 生效。
+    pub fn set_api_key(&mut self, api_key: String) {
+        self.session.set_api_key(api_key);
+    }
+
     pub fn set_reconnect_policy(&mut self, policy: RetryPolicy) {
         self.connection.set_retry_policy(policy);
     }
 
+    /// 设置心跳间隔，默认 60 秒，`None` 表示不发送心跳，下次连接时生效
+    pub fn set_heartbeat_interval(&mut self, heartbeat_interval: Option<Duration>) {
+        self.connection.set_heartbeat_interval(heartbeat_interval);
+    }
+
     pub fn on_ws_log<F>(&mut self, hook: F)
     where
         F: Fn(&str) + Send + Sync + 'static,
@@ -158,6 +232,16 @@ impl Notice {
         self.handler.set_log_hook_arc(hook);
     }
 
+    /// 监听重连状态，每次重连前调用，参数为 (第几次尝试, 重连前的等待时间)
+    ///
+    /// 可用于在界面上显示“重连中”，与连接彻底失败区分开。下次连接时生效。
+    pub fn on_reconnecting<F>(&mut self, hook: F)
+    where
+        F: Fn(u32, Duration) + Send + Sync + 'static,
+    {
+        self.connection.set_reconnect_hook(hook);
+    }
+
     /// 移除监听
     pub async fn off(&self, event_type: NoticeEventType) {
         self.handler
@@ -172,8 +256,9 @@ impl Notice {
         F: Fn(NoticeMsg) + Send + Sync + 'static,
     {
         self.add_listener(NoticeEventType::Msg, move |event: NoticeEventData| {
-            let NoticeEventData::Msg(msg) = event;
-            listener(msg);
+            if let NoticeEventData::Msg(msg) = event {
+                listener(msg);
+            }
         })
         .await;
     }
@@ -199,15 +284,13 @@ impl Notice {
     pub async fn count(&self) -> Result<NoticeCount, Error> {
         let url = build_http_path(
             "notifications/unread/count",
-            &[("apiKey", self.api_key.clone())],
+            &[("apiKey", self.session.api_key())],
         );
         let resp = get(&url).await?;
         if let Some(code) = resp["code"].as_i64()
             && code != 0
         {
-            return Err(Error::Api(
-                resp["msg"].as_str().unwrap_or("Api error").to_string(),
-            ));
+            return Err(Error::from_response(&resp, "Api error"));
         }
         let payload = if resp.get("data").is_some() {
             &resp["data"]
@@ -219,29 +302,66 @@ impl Notice {
         Ok(count)
     }
 
-    /// 获取消息列表
+    /// 获取消息列表，即服务端默认的第一页
     ///
     /// * `type` 消息类型
     ///
     /// 返回消息列表
     pub async fn list(&self, notice_type: NoticeType) -> Result<NoticeList, Error> {
-        let url = build_http_path(
-            "api/getNotifications",
-            &[
-                ("apiKey", self.api_key.clone()),
-                ("type", notice_type.as_str().to_string()),
-            ],
-        );
-        let resp = get(&url).await?;
+        let (list, _) = self.fetch_list(&notice_type, &[]).await?;
+        Ok(list)
+    }
 
-        let data_array = resp["data"]
-            .as_array()
-            .ok_or_else(|| Error::Api("Data is not an array".to_string()))?;
+    /// 分页获取消息列表
+    ///
+    /// * `type` 消息类型
+    /// * `page` 页码
+    /// * `size` 每页数量
+    ///
+    /// 返回 [NoticePage]，服务端返回分页信息时带有总页数
+    pub async fn list_page(
+        &self,
+        notice_type: NoticeType,
+        page: u32,
+        size: u32,
+    ) -> Result<NoticePage, Error> {
+        let (list, page_count) = self
+            .fetch_list(
+                &notice_type,
+                &[("p", page.to_string()), ("size", size.to_string())],
+            )
+            .await?;
+        Ok(NoticePage::new(list, page, size, page_count))
+    }
+
+    /// 请求消息列表，返回 (消息列表, 总页数)
+    async fn fetch_list(
+        &self,
+        notice_type: &NoticeType,
+        params: &[(&str, String)],
+    ) -> Result<(NoticeList, Option<u32>), Error> {
+        let mut query = vec![
+            ("apiKey", self.session.api_key()),
+            ("type", notice_type.as_str().to_string()),
+        ];
+        query.extend(params.iter().cloned());
+        let resp = get(&build_http_path("api/getNotifications", &query)).await?;
+
+        if let Some(code) = resp["code"].as_i64()
+            && code != 0
+        {
+            return Err(Error::from_response(&resp, "Api error"));
+        }
+
+        let data_array = as_array_or_empty(&resp["data"])?;
         let list: Vec<NoticeItem> = data_array
             .iter()
-            .map(|item| NoticeItem::from_value(item, &notice_type))
+            .map(|item| NoticeItem::from_value(item, notice_type))
             .collect::<Result<Vec<_>, _>>()?;
-        Ok(list)
+        let page_count = resp["pagination"]["paginationPageCount"]
+            .as_u64()
+            .map(|count| count as u32);
+        Ok((list, page_count))
     }
 
     /// 已读指定类型消息
@@ -252,16 +372,14 @@ impl Notice {
     pub async fn make_read(&self, notice_type: NoticeType) -> Result<bool, Error> {
         let url = build_http_path(
             &format!("notifications/make-read/{}", notice_type.as_str()),
-            &[("apiKey", self.api_key.clone())],
+            &[("apiKey", self.session.api_key())],
         );
         let resp = get(&url).await?;
 
         if let Some(code) = resp["code"].as_i64()
             && code != 0
         {
-            return Err(Error::Api(
-                resp["msg"].as_str().unwrap_or("Api error").to_string(),
-            ));
+            return Err(Error::from_response(&resp, "Api error"));
         }
 
         Ok(true)
@@ -271,25 +389,122 @@ impl Notice {
     pub async fn read_all(&self) -> Result<bool, Error> {
         let url = build_http_path(
             "notifications/all-read",
-            &[("apiKey", self.api_key.clone())],
+            &[("apiKey", self.session.api_key())],
         );
         let resp = get(&url).await?;
         if let Some(code) = resp["code"].as_i64()
             && code != 0
         {
-            return Err(Error::Api(
-                resp["msg"].as_str().unwrap_or("Api error").to_string(),
-            ));
+            return Err(Error::from_response(&resp, "Api error"));
         }
         Ok(true)
     }
+
+    /// 获取所有类型的未读通知
+    ///
+    /// 根据 [`Notice::count`] 的结果只查询有未读的类型。
+    ///
+    /// 返回未读通知列表
+    pub async fn all_unread(&self) -> Result<NoticeList, Error> {
+        let count = self.count().await?;
+        let types = [
+            (NoticeType::Point, count.point),
+            (NoticeType::Commented, count.commented),
+            (NoticeType::Reply, count.reply),
+            (NoticeType::At, count.at),
+            (NoticeType::Following, count.following + count.newFollower),
+            (NoticeType::Broadcast, count.broadcast),
+            (NoticeType::System, count.sysAnnounce),
+        ];
+
+        let mut unread = Vec::new();
+        for (notice_type, cnt) in types {
+            if cnt == 0 {
+                continue;
+            }
+            let list = self.list(notice_type).await?;
+            unread.extend(list.into_iter().filter(|item| !item.has_read()));
+        }
+        Ok(unread)
+    }
+
+    /// 订阅通知流
+    ///
+    /// 先推送当前所有未读通知（[`NoticeEventData::Item`]），再推送 WebSocket 实时消息（[`NoticeEventData::Msg`]）。
+    /// 实时消息需要调用 [`Notice::connect`] 建立连接后才会产生。
+    /// 丢弃返回的流即取消订阅。
+    ///
+    /// 返回通知事件流 [NoticeSubscription]
+    pub async fn subscribe_with_backfill(&self) -> Result<NoticeSubscription, Error> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let emitter = self.handler.get_emitter();
+        let listener = emitter
+            .add_listener_handle(NoticeEventType::Msg, move |event: NoticeEventData| {
+                let _ = tx.send(event);
+            })
+            .await;
+
+        let backfill = match self.all_unread().await {
+            Ok(backfill) => backfill,
+            Err(e) => {
+                emitter
+                    .remove_listener_handle(&NoticeEventType::Msg, &listener)
+                    .await;
+                return Err(e);
+            }
+        };
+        let live = stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|event| (event, rx))
+        });
+
+        Ok(NoticeSubscription {
+            inner: Box::pin(
+                stream::iter(backfill.into_iter().map(NoticeEventData::Item)).chain(live),
+            ),
+            emitter,
+            listener,
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::{NoticeEventData, NoticeEventType, parse_notice_message};
+    use crate::model::notice::{NoticeItem, NoticePage, NoticeType};
     use serde_json::json;
 
+    #[test]
+    fn notice_page_prefers_page_count() {
+        assert!(NoticePage::new(Vec::new(), 1, 20, Some(3)).has_more);
+        assert!(!NoticePage::new(Vec::new(), 3, 20, Some(3)).has_more);
+        assert!(!NoticePage::new(Vec::new(), 1, 20, None).has_more);
+    }
+
+    #[test]
+    fn parse_broadcast_notice_item() {
+        let item = json!({
+            "oId": "1",
+            "dataId": "1700000000000",
+            "dataType": 9,
+            "url": "https://fishpi.cn/article/1700000000000",
+            "articleTitle": "同城聚会",
+            "authorName": "alice",
+            "articlePerfect": 0,
+            "articleTagObjs": [],
+            "hasRead": false,
+            "createTime": "2024-01-01 00:00:00"
+        });
+
+        match NoticeItem::from_value(&item, &NoticeType::Broadcast).expect("should parse") {
+            NoticeItem::Broadcast(n) => {
+                assert_eq!(n.title, "同城聚会");
+                assert_eq!(n.author, "alice");
+                assert!(!n.perfect);
+            }
+            other => panic!("unexpected item: {:?}", other),
+        }
+    }
+
     #[test]
     fn parse_notice_warn_broadcast() {
         let payload = json!({
@@ -303,6 +518,7 @@ mod tests {
         assert!(matches!(event_type, NoticeEventType::Msg));
         match event {
             NoticeEventData::Msg(msg) => assert_eq!(msg.content.as_deref(), Some("hello")),
+            other => panic!("unexpected event: {:?}", other),
         }
     }
 
@@ -79,12 +79,7 @@ impl Reaction {
         if let Some(code) = resp["code"].as_i64()
             && code != 0
         {
-            return Err(Error::Api(
-                resp["msg"]
-                    .as_str()
-                    .unwrap_or("Reaction API error")
-                    .to_string(),
-            ));
+            return Err(Error::from_response(&resp, "Reaction API error"));
         }
 
         ReactionMutationResult::from_value(&resp["data"])
@@ -11,7 +11,10 @@
 //! # 方法列表
 //!
 //! - [`Redpacket::new`] - 创建新的红包客户端实例。
-//! - [`Redpacket::open`] - 打开一个红包。
+//! - [`Redpacket::with_session`] - 使用共享的登录会话创建实例。
+//! - [`Redpacket::set_session`] - 设置共享的登录会话。
+//! - [`Redpacket::open`] - 打开一个红包，返回领取结果。
+//! - [`Redpacket::info`] - 查询红包的领取情况，不打开红包。
 //! - [`Redpacket::send`] - 发送一个红包。
 //!
 //! # 示例
@@ -37,62 +40,140 @@
 //!
 //!     // 打开红包
 //!     let info = redpacket.open("redpacket_id", Some(GestureType::Paper)).await?;
-//!     println!("Opened redpacket: {:?}", info);
+//!     println!("Opened redpacket: {:?} {:?}", info.outcome, info.amount);
 //!
 //!     Ok(())
 //! }
 //! ```
-use serde_json::json;
+use serde::Serialize;
+use serde_json::Value;
+
+use std::sync::Arc;
 
 use crate::api::chatroom::ChatRoom;
-use crate::model::redpacket::{GestureType, RedPacket, RedPacketInfo};
+use crate::api::session::Session;
+use crate::model::chatroom::{ChatContentType, ChatRoomMessageMode};
+use crate::model::redpacket::{
+    GestureType, RedPacket, RedPacketInfo, RedPacketMessage, RedPacketOpenResult,
+};
 use crate::utils::error::Error;
-use crate::utils::post;
+use crate::utils::{post_with_key, with_api_key};
+
+/// 打开红包请求体
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OpenBody<'a> {
+    o_id: &'a str,
+    gesture: Option<u8>,
+}
+
+/// 发送红包时写入消息内容的红包数据
+#[derive(Serialize)]
+struct SendBody<'a> {
+    r#type: &'a str,
+    money: u32,
+    count: u32,
+    msg: &'a str,
+    recivers: &'a [String],
+    gesture: Option<u8>,
+}
 
 pub struct Redpacket {
-    api_key: String,
     chatroom: ChatRoom,
+    session: Arc<Session>,
 }
 
 impl Redpacket {
     pub fn new(api_key: String) -> Self {
+        Self::with_session(Session::new(api_key))
+    }
+
+    /// 使用共享的登录会话创建实例，API key 从会话读取
+    pub fn with_session(session: Arc<Session>) -> Self {
         Self {
-            api_key: api_key.clone(),
-            chatroom: ChatRoom::new(api_key),
+            chatroom: ChatRoom::with_session(Arc::clone(&session)),
+            session,
         }
     }
 
+    /// 重新设置 API key，共享同一会话的模块一并生效
+    pub fn set_api_key(&mut self, api_key: String) {
+        self.session.set_api_key(api_key);
+    }
+
+    /// 设置共享的登录会话，之后从该会话读取 API key 并识别领取记录中的当前用户
+    pub fn set_session(&mut self, session: Arc<Session>) {
+        self.chatroom.set_session(Arc::clone(&session));
+        self.session = session;
+    }
+
     /// 打开一个红包
     ///
     /// * `oId` 红包消息 Id
     /// * `gesture` 猜拳类型 [GestureType]
     ///
-    /// [RedPacketInfo]返回红包信息
+    /// 返回 [RedPacketOpenResult]，包含红包信息、当前用户获得的积分及猜拳输赢等结果；
+    /// 若已领取过该红包，返回 [Error::RedPacketAlreadyClaimed]
     pub async fn open(
         &self,
         oid: &str,
         gesture: Option<GestureType>,
-    ) -> Result<RedPacketInfo, Error> {
+    ) -> Result<RedPacketOpenResult, Error> {
         let url = "chat-room/red-packet/open".to_string();
 
-        let data = json!({
-            "oId": oid,
-            "gesture": gesture.map(|g| g as u8),
-            "apiKey": self.api_key
-        });
+        let data = OpenBody {
+            o_id: oid,
+            gesture: gesture.map(|g| g as u8),
+        };
 
-        let resp = post(&url, Some(data)).await?;
+        let resp = post_with_key(&url, &data, &self.session.api_key()).await?;
 
         if let Some(code) = resp.get("code").and_then(|c| c.as_i64())
             && code != 0
         {
-            return Err(Error::Api(
-                resp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+            let msg = resp["msg"].as_str().unwrap_or("API error").to_string();
+            if is_already_claimed(&msg) {
+                return Err(Error::RedPacketAlreadyClaimed(msg));
+            }
+            return Err(Error::from_response(&resp, "API error"));
         }
 
-        let red_packet_info: RedPacketInfo = RedPacketInfo::from_value(&resp)?;
-        Ok(red_packet_info)
+        // 红包已经打开，获取用户名失败时只是无法判断结果，不影响返回
+        let user_name = self.session.whoami().await.ok();
+        RedPacketOpenResult::from_value(&resp, user_name.as_deref())
+    }
+
+    /// 查询红包当前的领取情况，不会打开红包
+    ///
+    /// 通过聊天室消息接口读取红包消息，服务端在消息内容中记录最新的领取情况（`got` 与 `who`），
+    /// 可用于断线重连后补齐错过的红包状态。
+    ///
+    /// * `oid` 红包消息 Id
+    ///
+    /// 返回红包信息 [RedPacketInfo]；消息不存在或不是红包时返回 [Error::Api]，
+    /// 消息内容中没有领取记录时返回 [Error::Parse]
+    pub async fn info(&self, oid: &str) -> Result<RedPacketInfo, Error> {
+        let messages = self
+            .chatroom
+            .get_msg_around(
+                oid,
+                ChatRoomMessageMode::Context,
+                1,
+                ChatContentType::Markdown,
+            )
+            .await?;
+        let msg = messages
+            .iter()
+            .find(|msg| msg.oId == oid)
+            .ok_or_else(|| Error::api(format!("红包消息不存在: {}", oid)))?;
+
+        let red_packet = red_packet_status(&msg.content)
+            .ok_or_else(|| Error::api(format!("消息不是红包: {}", oid)))??;
+        Ok(RedPacketInfo::from_message(
+            &red_packet,
+            &msg.userName,
+            &msg.userAvatarURL,
+        ))
     }
 
     /// 发送一个红包
@@ -100,15 +181,17 @@ impl Redpacket {
     /// #### 参数
     /// * `redpacket` 红包对象 [RedPacket]
     pub async fn send(&self, redpacket: &RedPacket) -> Result<(), Error> {
-        let data = json!({
-            "type": redpacket.r#type.as_str(),
-            "money": redpacket.money,
-            "count": redpacket.count,
-            "msg": redpacket.msg,
-            "recivers": redpacket.recivers,
-            "gesture": redpacket.gesture.clone().map(|g| g as u8),
-            "apiKey": self.api_key
-        });
+        let data = with_api_key(
+            &SendBody {
+                r#type: redpacket.r#type.as_str(),
+                money: redpacket.money,
+                count: redpacket.count,
+                msg: &redpacket.msg,
+                recivers: &redpacket.recivers,
+                gesture: redpacket.gesture.clone().map(|g| g as u8),
+            },
+            &self.session.api_key(),
+        )?;
 
         self.chatroom
             .send(format!("[redpacket]{}[/redpacket]", data))
@@ -116,3 +199,61 @@ impl Redpacket {
         Ok(())
     }
 }
+
+/// 从红包消息内容中读取领取状态
+///
+/// 内容不是红包时返回 `None`；是红包但缺少 `got` 或 `who` 领取记录时返回错误，不补全数据。
+fn red_packet_status(content: &Value) -> Option<Result<RedPacketMessage, Error>> {
+    let data = match content {
+        Value::String(s) => serde_json::from_str::<Value>(s).ok()?,
+        other => other.clone(),
+    };
+    if data["msgType"].as_str() != Some("redPacket") {
+        return None;
+    }
+    if !data["got"].is_u64() || !data["who"].is_array() {
+        return Some(Err(Error::Parse(
+            "Red packet message has no claim data".to_string(),
+        )));
+    }
+    Some(RedPacketMessage::from_value(&data))
+}
+
+/// 判断打开红包的错误信息是否表示已经领取过
+fn is_already_claimed(msg: &str) -> bool {
+    msg.contains("已领取") || msg.contains("已经领取") || msg.contains("领过")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_already_claimed, red_packet_status};
+    use crate::utils::error::Error;
+    use serde_json::json;
+
+    #[test]
+    fn red_packet_status_requires_claim_data() {
+        let content = json!({
+            "msgType": "redPacket", "type": "random", "count": 2, "got": 1, "money": 32,
+            "msg": "摸鱼", "senderId": "7", "recivers": [],
+            "who": [{ "userId": "8", "userName": "bob", "avatar": "", "userMoney": 10, "time": "" }]
+        });
+        let rp = red_packet_status(&json!(content.to_string()))
+            .expect("is a red packet")
+            .expect("has claim data");
+        assert_eq!((rp.got, rp.who.len()), (1, 1));
+
+        let raw = json!({ "msgType": "redPacket", "count": 2, "money": 32, "msg": "摸鱼" });
+        assert!(matches!(
+            red_packet_status(&raw),
+            Some(Err(Error::Parse(_)))
+        ));
+        assert!(red_packet_status(&json!("hello")).is_none());
+    }
+
+    #[test]
+    fn already_claimed_message_is_detected() {
+        assert!(is_already_claimed("你已领取过这个红包"));
+        assert!(is_already_claimed("已经领取"));
+        assert!(!is_already_claimed("红包不存在"));
+    }
+}
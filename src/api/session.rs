@@ -0,0 +1,196 @@
+//! 登录会话模块
+//!
+//! 这个模块提供了各子模块共享的登录会话，保存 API key 与懒加载的当前用户身份（用户名、用户 Id），
+//! 供 `whoami`、判断自己发送的消息、提及过滤等功能复用同一份缓存。
+//!
+//! # 主要组件
+//!
+//! - [`Session`] - 登录会话，通过 `Arc<Session>` 在 `User`、`ChatRoom`、`Chat` 之间共享。
+//! - [`SessionProfile`] - 缓存的当前用户身份。
+//! - [`SavedSession`] - 持久化到本地文件的会话，见 [`crate::FishPi::save_session`]。
+//!
+//! # 方法列表
+//!
+//! - [`Session::new`] - 创建新的会话。
+//! - [`Session::api_key`] - 获取当前 API key。
+//! - [`Session::set_api_key`] - 重新设置 API key，并清空身份缓存。
+//! - [`Session::profile`] - 获取当前用户身份，首次调用时请求接口。
+//! - [`Session::cached_profile`] - 获取已缓存的用户身份，不发起请求。
+//! - [`Session::update_profile`] - 使用用户信息刷新身份缓存。
+//! - [`Session::restore_profile`] - 使用已保存的身份恢复缓存。
+//! - [`Session::whoami`] - 获取当前用户名。
+//! - [`Session::is_self`] - 判断用户名是否为当前用户。
+//! - [`Session::is_mentioned`] - 判断内容中是否提及当前用户。
+//!
+//! # 示例
+//!
+//! ```rust,no_run
+//! use fishpi_sdk::api::session::Session;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let session = Session::new("your_api_key".to_string());
+//!     println!("I am {}", session.whoami().await?);
+//!     Ok(())
+//! }
+//! ```
+
+use std::sync::{Arc, Mutex, RwLock};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::model::user::{UserId, UserInfo};
+use crate::utils::{build_http_path, error::Error, get};
+
+/// 当前用户身份
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionProfile {
+    /// 用户 Id
+    pub user_id: UserId,
+    /// 用户名
+    pub username: String,
+    /// 用户昵称
+    pub nickname: String,
+}
+
+impl SessionProfile {
+    pub fn from_info(info: &UserInfo) -> Self {
+        Self {
+            user_id: info.o_id.clone(),
+            username: info.username().to_string(),
+            nickname: info.nickname().to_string(),
+        }
+    }
+}
+
+/// 持久化到本地文件的会话
+///
+/// 文件中保存明文 API key，请妥善保管。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SavedSession {
+    /// API key
+    pub api_key: String,
+    /// 已缓存的用户身份
+    #[serde(default)]
+    pub profile: Option<SessionProfile>,
+}
+
+impl SavedSession {
+    pub fn from_value(data: &Value) -> Result<Self, Error> {
+        serde_json::from_value(data.clone())
+            .map_err(|e| Error::Parse(format!("Failed to parse SavedSession: {}", e)))
+    }
+
+    pub fn to_value(&self) -> Result<Value, Error> {
+        serde_json::to_value(self)
+            .map_err(|e| Error::Parse(format!("Failed to serialize SavedSession: {}", e)))
+    }
+}
+
+/// 登录会话
+pub struct Session {
+    api_key: RwLock<String>,
+    profile: Mutex<Option<SessionProfile>>,
+}
+
+impl Session {
+    pub fn new(api_key: String) -> Arc<Self> {
+        Arc::new(Self {
+            api_key: RwLock::new(api_key),
+            profile: Mutex::new(None),
+        })
+    }
+
+    /// 获取当前 API key
+    pub fn api_key(&self) -> String {
+        self.api_key
+            .read()
+            .map(|key| key.clone())
+            .unwrap_or_default()
+    }
+
+    /// 重新设置 API key，并清空身份缓存
+    pub fn set_api_key(&self, api_key: String) {
+        if let Ok(mut guard) = self.api_key.write() {
+            *guard = api_key;
+        }
+        if let Ok(mut guard) = self.profile.lock() {
+            *guard = None;
+        }
+    }
+
+    /// 获取已缓存的用户身份，不发起请求
+    pub fn cached_profile(&self) -> Option<SessionProfile> {
+        self.profile.lock().ok().and_then(|guard| guard.clone())
+    }
+
+    /// 使用用户信息刷新身份缓存
+    pub fn update_profile(&self, info: &UserInfo) {
+        if let Ok(mut guard) = self.profile.lock() {
+            *guard = Some(SessionProfile::from_info(info));
+        }
+    }
+
+    /// 使用已保存的身份恢复缓存
+    ///
+    /// - `profile` 用户身份
+    pub fn restore_profile(&self, profile: SessionProfile) {
+        if let Ok(mut guard) = self.profile.lock() {
+            *guard = Some(profile);
+        }
+    }
+
+    /// 获取当前用户身份，首次调用时请求接口并缓存
+    pub async fn profile(&self) -> Result<SessionProfile, Error> {
+        if let Some(profile) = self.cached_profile() {
+            return Ok(profile);
+        }
+
+        let data = fetch_user_value(&self.api_key()).await?;
+        let info = UserInfo::from_value(&data)?;
+        self.update_profile(&info);
+        Ok(SessionProfile::from_info(&info))
+    }
+
+    /// 获取当前用户名
+    pub async fn whoami(&self) -> Result<String, Error> {
+        Ok(self.profile().await?.username)
+    }
+
+    /// 判断用户名是否为当前用户
+    ///
+    /// - `username` 用户名
+    pub async fn is_self(&self, username: &str) -> Result<bool, Error> {
+        Ok(self.whoami().await?.eq_ignore_ascii_case(username))
+    }
+
+    /// 判断内容中是否提及（`@用户名`）当前用户
+    ///
+    /// - `content` 消息内容
+    pub async fn is_mentioned(&self, content: &str) -> Result<bool, Error> {
+        let username = self.whoami().await?;
+        Ok(content.contains(&format!("@{}", username)))
+    }
+}
+
+/// 请求 `api/user` 获取登录账户信息原始数据
+pub(crate) async fn fetch_user_value(api_key: &str) -> Result<Value, Error> {
+    let mut resp = get(&build_http_path(
+        "api/user",
+        &[("apiKey", api_key.to_string())],
+    ))
+    .await?;
+
+    if resp["code"] != 0 {
+        return Err(Error::from_response(&resp, "API error"));
+    }
+
+    let data_value = if let Some(data_str) = resp["data"].as_str() {
+        serde_json::from_str(data_str).map_err(|e| Error::api(e.to_string()))?
+    } else {
+        resp["data"].take()
+    };
+
+    Ok(data_value)
+}
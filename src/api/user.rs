@@ -10,13 +10,21 @@
 //! ## 方法列表
 //!
 //! - [`User::new`] - 创建新的用户客户端实例。
+//! - [`User::from_saved`] - 使用已保存的会话创建用户客户端实例。
 //! - [`User::get_token`] - 获取当前 API token。
-//! - [`User::set_token`] - 重新设置请求 token。
+//! - [`User::set_token`] - 重新设置请求 token，保留已注册的监听器。
+//! - [`User::relogin`] - 重新设置请求 token 并重连已建立的 WebSocket 连接。
+//! - [`User::logout`] - 退出登录，断开连接并清空 token。
 //! - [`User::is_logined`] - 检查用户是否已登录（API key 是否为空）。
 //! - [`User::info`] - 返回登录账户信息。
+//! - [`User::session`] - 获取共享的登录会话。
+//! - [`User::whoami`] - 获取当前登录用户名。
+//! - [`User::get_blur_setting`] - 查询聊天室图片自动模糊设置。
+//! - [`User::set_blur_setting`] - 设置聊天室图片自动模糊。
 //! - [`User::emotions`] - 查询登录用户常用表情。
 //! - [`User::liveness`] - 查询登录用户当前活跃度。
 //! - [`User::is_checkin`] - 检查用户是否已经签到。
+//! - [`User::checkin_status`] - 查询连续签到天数与今日签到状态。
 //! - [`User::is_collected_liveness`] - 检查用户是否领取昨日活跃奖励。
 //! - [`User::reward_liveness`] - 领取昨日活跃度奖励。
 //! - [`User::transfer`] - 转账。
@@ -25,9 +33,17 @@
 //! - [`User::update_avatar`] - 修改用户头像。
 //! - [`User::update_user_info`] - 修改用户信息。
 //! - [`User::get_user`] - 获取其他用户信息。
+//! - [`User::info_by_oid`] - 通过用户 oId 获取用户信息。
+//! - [`User::batch_users`] - 并发获取多个用户信息。
 //! - [`User::report`] - 举报。
 //! - [`User::upload`] - 上传文件。
+//! - [`User::upload_with_progress`] - 上传文件并报告进度。
+//! - [`User::upload_bytes`] - 上传内存中的文件。
 //! - [`User::get_points`] - 获取用户积分。
+//! - [`User::relationship`] - 查询与其他用户的关注关系。
+//! - [`User::followers`] - 分页获取用户的粉丝列表。
+//! - [`User::followings`] - 分页获取用户的关注列表。
+//! - [`User::metals`] - 获取用户拥有的全部勋章。
 //!
 //! ## 示例
 //!
@@ -72,14 +88,81 @@ use crate::api::chatroom::ChatRoom;
 use crate::api::comment::Comment;
 use crate::api::notice::Notice;
 use crate::api::redpacket::Redpacket;
-use crate::model::misc::{Report, UploadResult};
-use crate::model::user::{UpdateUserInfoParams, UserInfo, UserPoint};
+use crate::api::session::{SavedSession, Session, fetch_user_value};
+use crate::api::ws::WebSocketError;
+use crate::model::misc::{Report, UploadResult, UserLite};
+use crate::model::user::{
+    CheckinStatus, FollowList, Metal, Relationship, TransferResult, UpdateUserInfoParams, UserInfo,
+    UserPoint, to_metal,
+};
 use crate::utils::error::Error;
-use crate::utils::{ResponseResult, build_http_path, get, post, upload_files};
-use serde_json::{Value, json};
+use crate::utils::{
+    ResponseResult, build_http_path, get, post_with_key, upload_bytes, upload_files,
+    upload_files_with_progress,
+};
+use futures_util::{StreamExt, stream};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::sync::Arc;
+
+const FOLLOW_PAGE_SIZE: u32 = 50;
+/// 查询关注关系时最多翻阅的关注列表页数
+const MAX_FOLLOW_PAGES: u32 = 20;
+
+/// [`User::batch_users`] 查询用户信息的最大并发数
+pub const BATCH_USERS_CONCURRENCY: usize = 4;
+
+/// `api/settings/function` 一并提交的功能设置字段，缺少的字段会被服务端重置为默认值
+const FUNCTION_SETTING_FIELDS: &[&str] = &[
+    "userListPageSize",
+    "userCommentViewMode",
+    "userAvatarViewMode",
+    "userListViewMode",
+    "userNotifyStatus",
+    "userSubMailStatus",
+    "userKeyboardShortcutsStatus",
+    "userReplyWatchArticleStatus",
+    "userForwardPageStatus",
+    "userIndexRedirectURL",
+];
+
+/// 转账请求体
+#[derive(Serialize)]
+struct TransferBody<'a> {
+    username: &'a str,
+    amount: u32,
+    memo: &'a str,
+}
+
+/// 关注/取消关注用户请求体
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FollowBody<'a> {
+    following_id: &'a str,
+}
+
+/// 修改头像请求体
+#[derive(Serialize)]
+struct AvatarBody<'a> {
+    #[serde(rename = "userAvatarURL")]
+    user_avatar_url: &'a str,
+}
+
+/// 修改用户信息请求体
+#[derive(Serialize)]
+struct ProfileBody<'a> {
+    #[serde(rename = "userNickname")]
+    user_nickname: &'a Option<String>,
+    #[serde(rename = "userURL")]
+    user_url: &'a Option<String>,
+    #[serde(rename = "userIntro")]
+    user_intro: &'a Option<String>,
+    #[serde(rename = "userTag")]
+    user_tag: &'a Option<String>,
+}
 
 pub struct User {
-    api_key: String,
+    session: Arc<Session>,
     pub chatroom: ChatRoom,
     pub chat: Chat,
     pub breezemoon: BreezeMoon,
@@ -91,73 +174,152 @@ pub struct User {
 
 impl User {
     pub fn new(api_key: String) -> Self {
+        let session = Session::new(api_key);
+
         Self {
-            api_key: api_key.clone(),
-            chatroom: ChatRoom::new(api_key.clone()),
-            chat: Chat::new(api_key.clone()),
-            breezemoon: BreezeMoon::new(api_key.clone()),
-            article: Article::new(api_key.clone()),
-            notice: Notice::new(api_key.clone()),
-            redpacket: Redpacket::new(api_key.clone()),
-            comment: Comment::new(api_key.clone()),
+            chatroom: ChatRoom::with_session(Arc::clone(&session)),
+            chat: Chat::with_session(Arc::clone(&session)),
+            breezemoon: BreezeMoon::with_session(Arc::clone(&session)),
+            article: Article::with_session(Arc::clone(&session)),
+            notice: Notice::with_session(Arc::clone(&session)),
+            redpacket: Redpacket::with_session(Arc::clone(&session)),
+            comment: Comment::with_session(Arc::clone(&session)),
+            session,
+        }
+    }
+
+    /// 使用已保存的会话创建用户客户端实例，并恢复缓存的用户身份
+    ///
+    /// - `saved` 由 [`crate::FishPi::load_session`] 读取的会话
+    pub fn from_saved(saved: SavedSession) -> Self {
+        let user = Self::new(saved.api_key);
+        if let Some(profile) = saved.profile {
+            user.session.restore_profile(profile);
         }
+        user
     }
 
-    pub fn get_token(&self) -> &str {
-        &self.api_key
+    /// 当前 API key，读取自共享会话
+    pub fn get_token(&self) -> String {
+        self.session.api_key()
     }
 
     /// 重新设置请求token
+    ///
+    /// 各子模块共享同一会话，更新后立即使用新 token，已注册的监听器会保留；
+    /// 已建立的 WebSocket 连接仍使用旧 token，需要重连才能生效，可使用 [`User::relogin`]。
     pub fn set_token(&mut self, token: String) {
-        self.api_key = token.clone();
-        self.chatroom.set_api_key(token.clone());
-        self.chat = Chat::new(token.clone());
-        self.breezemoon = BreezeMoon::new(token.clone());
-        self.article = Article::new(token.clone());
-        self.notice = Notice::new(token.clone());
-        self.redpacket = Redpacket::new(token.clone());
-        self.comment = Comment::new(token);
+        self.session.set_api_key(token);
     }
 
+    /// 重新设置请求token，并重连已建立的 WebSocket 连接
+    ///
+    /// 聊天室、私聊、通知的监听器会保留，重连后继续生效。
+    ///
+    /// - `token` 新的 API key
+    pub async fn relogin(&mut self, token: String) -> Result<(), WebSocketError> {
+        self.set_token(token);
+
+        if self.chatroom.is_connected() {
+            self.chatroom.reconnect().await?;
+        }
+        if self.chat.is_connected() {
+            let target = self.chat.target().map(|t| t.to_string());
+            self.chat.reconnect(target).await?;
+        }
+        if self.notice.is_connected() {
+            self.notice.reconnect().await?;
+        }
+        Ok(())
+    }
+
+    /// 退出登录
+    ///
+    /// 断开聊天室、私聊、通知以及 [`Article::listen`] 建立的 WebSocket 连接，清空所有子模块的 API key
+    /// 与共享会话的身份缓存，之后 [`User::is_logined`] 返回 `false`。
+    /// Cookie 为全局共享，不会被清空，需要时调用 [`crate::utils::clear_cookies`]。
+    /// 服务端没有注销 API key 的接口，已泄露的 key 需要在网页端修改密码使其失效。
+    pub fn logout(&mut self) {
+        self.chatroom.disconnect();
+        self.chat.disconnect();
+        self.notice.disconnect();
+        self.article.disconnect();
+        self.set_token(String::new());
+    }
+
+    /// 是否已登录，只检查 API key 是否为空
+    ///
+    /// API key 过期或失效时，请求会返回 [Error::Unauthorized]，可据此重新登录。
     pub fn is_logined(&self) -> bool {
-        !self.api_key.is_empty()
+        !self.session.api_key().is_empty()
     }
 
     /// 返回登录账户信息，需要先登录或设置有效api_key
     pub async fn info(&self) -> Result<UserInfo, Error> {
-        let mut resp = get(&build_http_path(
-            "api/user",
-            &[("apiKey", self.api_key.clone())],
-        ))
-        .await?;
+        let data_value = self.info_value().await?;
+        let info = UserInfo::from_value(&data_value)?;
+        self.session.update_profile(&info);
+        Ok(info)
+    }
+
+    /// 获取共享的登录会话
+    pub fn session(&self) -> Arc<Session> {
+        self.session.clone()
+    }
+
+    /// 获取当前登录用户名，使用会话缓存
+    pub async fn whoami(&self) -> Result<String, Error> {
+        self.session.whoami().await
+    }
+
+    /// 获取登录账户信息原始数据
+    async fn info_value(&self) -> Result<Value, Error> {
+        fetch_user_value(&self.session.api_key()).await
+    }
+
+    /// 查询聊天室图片自动模糊设置
+    ///
+    /// 返回是否开启自动模糊
+    pub async fn get_blur_setting(&self) -> Result<bool, Error> {
+        let data = self.info_value().await?;
+        let blur = &data["isAutoBlur"];
+        blur.as_i64()
+            .map(|v| v == 1)
+            .or(blur.as_bool())
+            .ok_or_else(|| Error::Parse("Missing isAutoBlur in user info".to_string()))
+    }
+
+    /// 设置聊天室图片自动模糊
+    ///
+    /// 服务端按整张功能设置表单更新，这里先读取账户信息中的当前设置，只修改 `isAutoBlur` 后整体提交；
+    /// 账户信息缺少任一功能设置字段时返回 [Error::Parse]，不提交，避免其它设置被重置。
+    ///
+    /// - `enable` 是否开启自动模糊
+    ///
+    /// 返回执行结果
+    pub async fn set_blur_setting(&self, enable: bool) -> Result<bool, Error> {
+        let info = self.info_value().await?;
+        let data = function_settings(&info, enable)?;
+
+        let resp = post_with_key("api/settings/function", &data, &self.session.api_key()).await?;
 
         if resp["code"] != 0 {
-            return Err(Error::Api(
-                resp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+            return Err(Error::from_response(&resp, "API error"));
         }
 
-        let data_value = if let Some(data_str) = resp["data"].as_str() {
-            serde_json::from_str(data_str).map_err(|e| Error::Api(e.to_string()))?
-        } else {
-            resp["data"].take()
-        };
-
-        UserInfo::from_value(&data_value)
+        Ok(true)
     }
 
     /// 查询登录用户常用表情
     pub async fn emotions(&self) -> Result<Vec<String>, Error> {
         let mut resp = get(&build_http_path(
             "users/emotions",
-            &[("apiKey", self.api_key.clone())],
+            &[("apiKey", self.session.api_key())],
         ))
         .await?;
 
         if resp["code"] != 0 {
-            return Err(Error::Api(
-                resp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+            return Err(Error::from_response(&resp, "API error"));
         }
 
         let data: Vec<Value> = serde_json::from_value(resp["data"].take())
@@ -176,30 +338,14 @@ impl User {
 
     /// 查询登录用户当前活跃度，请求频率请至少 10 分钟一次
     pub async fn liveness(&self) -> Result<u32, Error> {
-        let resp = get(&build_http_path(
-            "user/liveness",
-            &[("apiKey", self.api_key.clone())],
-        ))
-        .await?;
-
-        let liveness_raw = resp
-            .get("liveness")
-            .and_then(|v| {
-                v.as_f64()
-                    .or_else(|| v.as_u64().map(|n| n as f64))
-                    .or_else(|| v.as_i64().map(|n| n as f64))
-                    .or_else(|| v.as_str().and_then(|s| s.parse::<f64>().ok()))
-            })
-            .ok_or_else(|| Error::Api("Missing or invalid liveness".to_string()))?;
-
-        Ok(liveness_raw.max(0.0).round() as u32)
+        fetch_liveness(&self.session.api_key()).await
     }
 
     /// 检查用户是否已经签到
     pub async fn is_checkin(&self) -> Result<bool, Error> {
         let resp = get(&build_http_path(
             "user/isCheckin",
-            &[("apiKey", self.api_key.clone())],
+            &[("apiKey", self.session.api_key())],
         ))
         .await?;
 
@@ -207,63 +353,66 @@ impl User {
         Ok(is_checkin)
     }
 
+    /// 查询签到状态
+    ///
+    /// 连续签到天数读取自登录账户信息，今日是否签到见 [`User::is_checkin`]。
+    ///
+    /// 返回 [CheckinStatus]
+    pub async fn checkin_status(&self) -> Result<CheckinStatus, Error> {
+        let data = self.info_value().await?;
+        let checked_in_today = self.is_checkin().await?;
+        Ok(CheckinStatus::from_value(&data, checked_in_today))
+    }
+
     /// 检查用户是否领取昨日活跃奖励
     pub async fn is_collected_liveness(&self) -> Result<bool, Error> {
-        let resp = get(&build_http_path(
-            "api/activity/is-collected-liveness",
-            &[("apiKey", self.api_key.clone())],
-        ))
-        .await?;
-
-        let is_rewarded: bool = resp["isLivenessRewarded"].as_bool().unwrap_or(false);
-        Ok(is_rewarded)
+        fetch_liveness_collected(&self.session.api_key()).await
     }
 
     /// 领取昨日活跃度奖励
+    ///
+    /// 返回领取到的积分数，已领取过时为 0
     pub async fn reward_liveness(&self) -> Result<u32, Error> {
-        let resp = get(&build_http_path(
-            "activity/yesterday-liveness-reward-api",
-            &[("apiKey", self.api_key.clone())],
-        ))
-        .await?;
-
-        let success: u32 = resp["sum"].as_u64().unwrap_or(0) as u32;
-        Ok(success)
+        claim_liveness_reward(&self.session.api_key()).await
     }
 
     /// 转账
-    pub async fn transfer(&self, username: &str, amount: u32, memo: &str) -> Result<bool, Error> {
-        let data = json!({
-            "username": username,
-            "amount": amount,
-            "memo": memo,
-            "apiKey": self.api_key,
-        });
+    ///
+    /// - `username` 收款用户名
+    /// - `amount` 转账积分
+    /// - `memo` 转账备注
+    ///
+    /// 返回 [TransferResult]；积分不足返回 [Error::InsufficientPoints]，
+    /// 收款用户不存在返回 [Error::UserNotFound]
+    pub async fn transfer(
+        &self,
+        username: &str,
+        amount: u32,
+        memo: &str,
+    ) -> Result<TransferResult, Error> {
+        let data = TransferBody {
+            username,
+            amount,
+            memo,
+        };
 
-        let resp = post("point/transfer", Some(data)).await?;
+        let resp = post_with_key("point/transfer", &data, &self.session.api_key()).await?;
 
         if resp["code"] != 0 {
-            return Err(Error::Api(
-                resp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+            return Err(transfer_error(&resp));
         }
 
-        Ok(true)
+        Ok(TransferResult::from_value(&resp))
     }
 
     /// 关注用户
     pub async fn follow(&self, following_id: &str) -> Result<bool, Error> {
-        let data = json!({
-            "followingId": following_id,
-            "apiKey": self.api_key,
-        });
+        let data = FollowBody { following_id };
 
-        let resp = post("follow/user", Some(data)).await?;
+        let resp = post_with_key("follow/user", &data, &self.session.api_key()).await?;
 
         if resp["code"] != 0 {
-            return Err(Error::Api(
-                resp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+            return Err(Error::from_response(&resp, "API error"));
         }
 
         Ok(true)
@@ -271,17 +420,12 @@ impl User {
 
     /// 取消关注用户
     pub async fn unfollow(&self, following_id: &str) -> Result<bool, Error> {
-        let data = json!({
-            "followingId": following_id,
-            "apiKey": self.api_key,
-        });
+        let data = FollowBody { following_id };
 
-        let resp = post("unfollow/user", Some(data)).await?;
+        let resp = post_with_key("unfollow/user", &data, &self.session.api_key()).await?;
 
         if resp["code"] != 0 {
-            return Err(Error::Api(
-                resp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+            return Err(Error::from_response(&resp, "API error"));
         }
 
         Ok(true)
@@ -289,17 +433,14 @@ impl User {
 
     /// 修改用户头像
     pub async fn update_avatar(&self, avatar_url: &str) -> Result<bool, Error> {
-        let data = json!({
-            "userAvatarURL": avatar_url,
-            "apiKey": self.api_key
-        });
+        let data = AvatarBody {
+            user_avatar_url: avatar_url,
+        };
 
-        let resp = post("api/settings/avatar", Some(data)).await?;
+        let resp = post_with_key("api/settings/avatar", &data, &self.session.api_key()).await?;
 
         if resp["code"] != 0 {
-            return Err(Error::Api(
-                resp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+            return Err(Error::from_response(&resp, "API error"));
         }
 
         Ok(true)
@@ -310,20 +451,17 @@ impl User {
     /// #### 参数
     /// * `params` 用户信息参数 [UpdateUserInfoParams]
     pub async fn update_user_info(&self, params: UpdateUserInfoParams) -> Result<bool, Error> {
-        let data = json!({
-            "userNickname": params.nickName,
-            "userURL": params.userUrl,
-            "userIntro": params.userIntro,
-            "userTag": params.userTag,
-            "apiKey": self.api_key,
-        });
+        let data = ProfileBody {
+            user_nickname: &params.nickName,
+            user_url: &params.userUrl,
+            user_intro: &params.userIntro,
+            user_tag: &params.userTag,
+        };
 
-        let resp = post("api/settings/profiles", Some(data)).await?;
+        let resp = post_with_key("api/settings/profiles", &data, &self.session.api_key()).await?;
 
         if resp["code"] != 0 {
-            return Err(Error::Api(
-                resp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+            return Err(Error::from_response(&resp, "API error"));
         }
 
         Ok(true)
@@ -337,20 +475,55 @@ impl User {
     pub async fn get_user(&self, username: &str) -> Result<UserInfo, Error> {
         let url = build_http_path(
             &format!("user/{}", username),
-            &[("apiKey", self.api_key.clone())],
+            &[("apiKey", self.session.api_key())],
         );
 
         let rsp = get(&url).await?;
 
         if rsp.get("code").and_then(|c| c.as_i64()).unwrap_or(0) != 0 {
-            return Err(Error::Api(
-                rsp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+            return Err(Error::from_response(&rsp, "API error"));
         }
 
         UserInfo::from_value(&rsp)
     }
 
+    /// 通过用户 oId 获取用户信息
+    ///
+    /// - `user_id` 用户 oId，如通知中的 `userId`
+    ///
+    /// 返回用户信息
+    pub async fn info_by_oid(&self, user_id: &str) -> Result<UserInfo, Error> {
+        let url = build_http_path(
+            "api/user/getInfoById",
+            &[
+                ("userId", user_id.to_string()),
+                ("apiKey", self.session.api_key()),
+            ],
+        );
+
+        let rsp = get(&url).await?;
+
+        if rsp.get("code").and_then(|c| c.as_i64()).unwrap_or(0) != 0 {
+            return Err(Error::from_response(&rsp, "API error"));
+        }
+
+        let data = rsp.get("data").filter(|v| v.is_object()).unwrap_or(&rsp);
+        UserInfo::from_value(data)
+    }
+
+    /// 并发获取多个用户的信息，同时最多 [`BATCH_USERS_CONCURRENCY`] 个请求
+    ///
+    /// - `usernames` 用户名列表
+    ///
+    /// 返回与 `usernames` 顺序一致的结果，单个用户查询失败不影响其他用户
+    pub async fn batch_users(&self, usernames: &[&str]) -> Vec<Result<UserInfo, Error>> {
+        stream::iter(usernames)
+            .map(|name| self.get_user(name))
+            .buffered(BATCH_USERS_CONCURRENCY)
+            .collect()
+            .await
+    }
+
     /// 举报
     ///
     /// - `data` 举报数据 [Report]
@@ -359,11 +532,7 @@ impl User {
     pub async fn report(&self, data: &Report) -> Result<ResponseResult, Error> {
         let url = "report".to_string();
 
-        let mut data_json = serde_json::to_value(data)
-            .map_err(|e| Error::Parse(format!("Failed to serialize Report: {}", e)))?;
-        data_json["apiKey"] = Value::String(self.api_key.clone());
-
-        let rsp = post(&url, Some(data_json)).await?;
+        let rsp = post_with_key(&url, data, &self.session.api_key()).await?;
 
         ResponseResult::from_value(&rsp)
     }
@@ -377,20 +546,52 @@ impl User {
         // 检查文件是否存在
         for file in &files {
             if !std::path::Path::new(file).exists() {
-                return Err(Error::Api(format!("File not exist: {}", file)));
+                return Err(Error::api(format!("File not exist: {}", file)));
             }
         }
 
         let url = "upload".to_string();
-        let rsp = upload_files(&url, files, &self.api_key).await?;
+        let rsp = upload_files(&url, files, &self.session.api_key()).await?;
 
-        if rsp.get("code").and_then(|c| c.as_i64()).unwrap_or(-1) != 0 {
-            return Err(Error::Api(
-                rsp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
-        }
+        parse_upload(&rsp)
+    }
+
+    /// 上传文件并报告进度
+    ///
+    /// 文件以流式方式发送，适合较大的文件，可用于绘制进度条。
+    ///
+    /// - `files` 文件路径列表
+    /// - `progress` 进度回调，参数为已发送字节数与总字节数
+    ///
+    /// 返回上传结果
+    pub async fn upload_with_progress<F>(
+        &self,
+        files: Vec<String>,
+        progress: F,
+    ) -> Result<UploadResult, Error>
+    where
+        F: Fn(u64, u64) + Send + Sync + 'static,
+    {
+        let rsp = upload_files_with_progress(
+            "upload",
+            files,
+            &self.session.api_key(),
+            Arc::new(progress),
+        )
+        .await?;
+
+        parse_upload(&rsp)
+    }
+
+    /// 上传内存中的文件
+    ///
+    /// - `files` 文件名与文件内容列表，如程序生成的截图
+    ///
+    /// 返回上传结果
+    pub async fn upload_bytes(&self, files: Vec<(String, Vec<u8>)>) -> Result<UploadResult, Error> {
+        let rsp = upload_bytes("upload", files, &self.session.api_key()).await?;
 
-        UploadResult::from_value(&rsp["data"])
+        parse_upload(&rsp)
     }
 
     /// 获取用户积分
@@ -401,11 +602,369 @@ impl User {
         let resp = get(&format!("user/{}/point", username)).await?;
 
         if resp.get("code").and_then(|c| c.as_i64()).unwrap_or(-1) != 0 {
-            return Err(Error::Api(
-                resp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+            return Err(Error::from_response(&resp, "API error"));
         }
 
         UserPoint::from_value(&resp)
     }
+
+    /// 查询与其他用户的关注关系
+    ///
+    /// 先翻阅对方的关注列表判断对方是否关注了自己，对方隐藏关注列表或列表超过 20 页时
+    /// 改为翻阅自己的粉丝列表；两者都无法确认时返回 [Error::Api]，不猜测结果。
+    ///
+    /// - `other` 对方用户名
+    ///
+    /// 返回关注关系 [Relationship]
+    pub async fn relationship(&self, other: &str) -> Result<Relationship, Error> {
+        let me = self.session.whoami().await?;
+        if me.eq_ignore_ascii_case(other) {
+            return Ok(Relationship::Oneself);
+        }
+
+        let following = self.get_user(other).await?.is_following();
+
+        let followed_by = match self.follow_list_contains(other, "followings", &me).await? {
+            Some(found) => found,
+            None => self
+                .follow_list_contains(&me, "followers", other)
+                .await?
+                .ok_or_else(|| {
+                    Error::api(format!(
+                        "无法确认 {} 是否关注了自己：关注列表已隐藏或超过 {} 页",
+                        other, MAX_FOLLOW_PAGES
+                    ))
+                })?,
+        };
+
+        Ok(Relationship::from_flags(following, followed_by))
+    }
+
+    /// 获取用户拥有的全部勋章（包含未佩戴）
+    ///
+    /// - `username` 用户名
+    ///
+    /// 返回勋章列表
+    pub async fn metals(&self, username: &str) -> Result<Vec<Metal>, Error> {
+        let url = build_http_path(
+            &format!("user/{}/metal", username),
+            &[("apiKey", self.session.api_key())],
+        );
+
+        let rsp = get(&url).await?;
+
+        if rsp.get("code").and_then(|c| c.as_i64()).unwrap_or(0) != 0 {
+            return Err(Error::from_response(&rsp, "API error"));
+        }
+
+        let raw = match &rsp["data"] {
+            Value::String(s) => s.clone(),
+            Value::Null => return Ok(Vec::new()),
+            other => other.to_string(),
+        };
+
+        to_metal(&raw).map_err(|e| Error::Parse(format!("Failed to parse metals: {}", e)))
+    }
+
+    /// 分页获取用户的粉丝列表
+    ///
+    /// - `username` 用户名
+    /// - `page` 页码
+    /// - `size` 每页数量
+    ///
+    /// 返回 [FollowList]，对方隐藏粉丝列表时 `hidden` 为 `true`
+    pub async fn followers(
+        &self,
+        username: &str,
+        page: u32,
+        size: u32,
+    ) -> Result<FollowList, Error> {
+        self.follow_list(username, "followers", page, size).await
+    }
+
+    /// 分页获取用户的关注列表
+    ///
+    /// - `username` 用户名
+    /// - `page` 页码
+    /// - `size` 每页数量
+    ///
+    /// 返回 [FollowList]，对方隐藏关注列表时 `hidden` 为 `true`
+    pub async fn followings(
+        &self,
+        username: &str,
+        page: u32,
+        size: u32,
+    ) -> Result<FollowList, Error> {
+        self.follow_list(username, "followings", page, size).await
+    }
+
+    /// 获取关注列表
+    ///
+    /// - `username` 用户名
+    /// - `kind` `followers` 或 `followings`
+    /// - `page` 页码
+    /// - `size` 每页数量
+    async fn follow_list(
+        &self,
+        username: &str,
+        kind: &str,
+        page: u32,
+        size: u32,
+    ) -> Result<FollowList, Error> {
+        let url = build_http_path(
+            &format!("api/user/{}/{}", username, kind),
+            &[
+                ("p", page.to_string()),
+                ("size", size.to_string()),
+                ("apiKey", self.session.api_key()),
+            ],
+        );
+
+        let rsp = get(&url).await?;
+        parse_follow_list(&rsp, page, size)
+    }
+
+    /// 翻阅关注列表，查找其中是否有指定用户
+    ///
+    /// - `username` 列表所属用户名
+    /// - `kind` `followers` 或 `followings`
+    /// - `target` 要查找的用户名
+    ///
+    /// 返回是否找到；列表被隐藏或超过 [`MAX_FOLLOW_PAGES`] 页仍未翻完时返回 `None`
+    async fn follow_list_contains(
+        &self,
+        username: &str,
+        kind: &str,
+        target: &str,
+    ) -> Result<Option<bool>, Error> {
+        for page in 1..=MAX_FOLLOW_PAGES {
+            let list = self
+                .follow_list(username, kind, page, FOLLOW_PAGE_SIZE)
+                .await?;
+            if list.hidden {
+                return Ok(None);
+            }
+            if list
+                .users
+                .iter()
+                .any(|u| u.user_name.eq_ignore_ascii_case(target))
+            {
+                return Ok(Some(true));
+            }
+            if list.users.len() < FOLLOW_PAGE_SIZE as usize {
+                return Ok(Some(false));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// 解析关注列表响应
+///
+/// 请求成功但响应中没有 `data` 列表时，说明对方隐藏了该列表，返回 [FollowList::hidden]；
+/// `code` 非 0 一律视为错误。
+fn parse_follow_list(rsp: &Value, page: u32, size: u32) -> Result<FollowList, Error> {
+    if rsp.get("code").and_then(|c| c.as_i64()).unwrap_or(0) != 0 {
+        return Err(Error::from_response(rsp, "API error"));
+    }
+
+    let Some(list) = rsp["data"].as_array() else {
+        return Ok(FollowList::hidden(page, size));
+    };
+    let users = list
+        .iter()
+        .filter(|u| u["userName"].is_string())
+        .map(UserLite::from_value)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(FollowList {
+        users,
+        page,
+        size,
+        hidden: false,
+    })
+}
+
+/// 解析上传接口响应
+fn parse_upload(rsp: &Value) -> Result<UploadResult, Error> {
+    if rsp.get("code").and_then(|c| c.as_i64()).unwrap_or(-1) != 0 {
+        return Err(Error::from_response(rsp, "API error"));
+    }
+
+    UploadResult::from_value(&rsp["data"])
+}
+
+/// 由账户信息中的当前功能设置构造完整的功能设置表单，只修改 `isAutoBlur`
+fn function_settings(info: &Value, auto_blur: bool) -> Result<Map<String, Value>, Error> {
+    let mut settings = Map::new();
+    for field in FUNCTION_SETTING_FIELDS {
+        let value = info
+            .get(*field)
+            .ok_or_else(|| Error::Parse(format!("Missing {} in user info", field)))?;
+        settings.insert(field.to_string(), value.clone());
+    }
+    settings.insert("isAutoBlur".to_string(), Value::from(u8::from(auto_blur)));
+    Ok(settings)
+}
+
+/// 查询当前活跃度
+pub(crate) async fn fetch_liveness(api_key: &str) -> Result<u32, Error> {
+    let resp = get(&build_http_path(
+        "user/liveness",
+        &[("apiKey", api_key.to_string())],
+    ))
+    .await?;
+
+    let liveness_raw = resp
+        .get("liveness")
+        .and_then(|v| {
+            v.as_f64()
+                .or_else(|| v.as_u64().map(|n| n as f64))
+                .or_else(|| v.as_i64().map(|n| n as f64))
+                .or_else(|| v.as_str().and_then(|s| s.parse::<f64>().ok()))
+        })
+        .ok_or_else(|| Error::api("Missing or invalid liveness".to_string()))?;
+
+    Ok(liveness_raw.max(0.0).round() as u32)
+}
+
+/// 查询是否已领取昨日活跃奖励
+pub(crate) async fn fetch_liveness_collected(api_key: &str) -> Result<bool, Error> {
+    let resp = get(&build_http_path(
+        "api/activity/is-collected-liveness",
+        &[("apiKey", api_key.to_string())],
+    ))
+    .await?;
+
+    Ok(resp["isLivenessRewarded"].as_bool().unwrap_or(false))
+}
+
+/// 领取昨日活跃奖励，返回领取到的积分数
+pub(crate) async fn claim_liveness_reward(api_key: &str) -> Result<u32, Error> {
+    let resp = get(&build_http_path(
+        "activity/yesterday-liveness-reward-api",
+        &[("apiKey", api_key.to_string())],
+    ))
+    .await?;
+
+    if let Some(code) = resp.get("code").and_then(|c| c.as_i64())
+        && code != 0
+    {
+        return Err(Error::from_response(&resp, "API error"));
+    }
+
+    Ok(resp["sum"].as_u64().unwrap_or(0) as u32)
+}
+
+/// 将转账失败的响应转换为错误，积分不足与用户不存在单独区分
+fn transfer_error(resp: &Value) -> Error {
+    let msg = resp["msg"].as_str().unwrap_or("API error").to_string();
+    if msg.contains("不足") {
+        Error::InsufficientPoints(msg)
+    } else if msg.contains("不存在") || msg.contains("未找到") {
+        Error::UserNotFound(msg)
+    } else {
+        Error::from_response(resp, "API error")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        FUNCTION_SETTING_FIELDS, ProfileBody, User, function_settings, parse_follow_list,
+        transfer_error,
+    };
+    use crate::utils::{error::Error, with_api_key};
+    use serde_json::json;
+
+    #[test]
+    fn follow_list_hidden_is_not_an_error() {
+        let list = parse_follow_list(
+            &json!({ "code": 0, "data": [{ "userName": "alice", "userNickname": "A" }] }),
+            1,
+            1,
+        )
+        .expect("should parse");
+        assert_eq!(list.users[0].user_name, "alice");
+        assert!(list.has_more());
+
+        let list = parse_follow_list(&json!({ "code": 0, "msg": "" }), 1, 20)
+            .expect("hidden list is not an error");
+        assert!(list.hidden);
+        assert!(list.users.is_empty());
+        assert!(!list.has_more());
+
+        // 失败响应不再按提示文字猜测是否隐藏
+        let err = parse_follow_list(&json!({ "code": -1, "msg": "该用户已隐藏关注列表" }), 1, 20)
+            .expect_err("non-zero code is an error");
+        assert_eq!(err.code(), Some(-1));
+    }
+
+    #[test]
+    fn submodules_share_one_session() {
+        let mut user = User::new("AbCdEf0123456789xyz".to_string());
+        user.article.set_api_key("ZyXwVu9876543210abc".to_string());
+        assert_eq!(user.get_token(), "ZyXwVu9876543210abc");
+        user.set_token("AbCdEf0123456789xyz".to_string());
+        assert_eq!(user.session().api_key(), "AbCdEf0123456789xyz");
+    }
+
+    #[test]
+    fn logout_clears_token() {
+        let mut user = User::new("AbCdEf0123456789xyz".to_string());
+        assert!(user.is_logined());
+        user.logout();
+        assert!(!user.is_logined());
+        assert_eq!(user.get_token(), "");
+        assert_eq!(user.session().api_key(), "");
+    }
+
+    #[test]
+    fn transfer_error_distinguishes_known_failures() {
+        let err = transfer_error(&json!({ "code": -1, "msg": "积分余额不足" }));
+        assert!(matches!(err, Error::InsufficientPoints(_)));
+        let err = transfer_error(&json!({ "code": -1, "msg": "用户不存在" }));
+        assert!(matches!(err, Error::UserNotFound(_)));
+        let err = transfer_error(&json!({ "code": -1, "msg": "转账失败" }));
+        assert_eq!(err.code(), Some(-1));
+    }
+
+    #[test]
+    fn profile_body_uses_server_field_names() {
+        let nickname = Some("A".to_string());
+        let body = ProfileBody {
+            user_nickname: &nickname,
+            user_url: &None,
+            user_intro: &None,
+            user_tag: &None,
+        };
+        assert_eq!(
+            with_api_key(&body, "key").expect("object"),
+            json!({
+                "userNickname": "A",
+                "userURL": null,
+                "userIntro": null,
+                "userTag": null,
+                "apiKey": "key",
+            })
+        );
+    }
+
+    #[test]
+    fn function_settings_keeps_other_fields() {
+        let mut info = json!({ "isAutoBlur": 0, "userName": "alice" });
+        for field in FUNCTION_SETTING_FIELDS {
+            info[*field] = json!(1);
+        }
+        let settings = function_settings(&info, true).expect("all fields present");
+        assert_eq!(settings["isAutoBlur"], json!(1));
+        assert_eq!(settings["userListPageSize"], json!(1));
+        assert!(!settings.contains_key("userName"));
+
+        info.as_object_mut().unwrap().remove("userListViewMode");
+        assert!(matches!(
+            function_settings(&info, true),
+            Err(Error::Parse(_))
+        ));
+    }
 }
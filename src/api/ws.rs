@@ -15,11 +15,14 @@
 //! # 方法列表
 //!
 //! - [`WebSocketClient::connect`] - 创建并连接 WebSocket。
+//! - [`WebSocketClient::connect_with_heartbeat`] - 创建并连接 WebSocket，定时发送心跳。
 //! - [`WebSocketClient::add_listener`] - 添加事件监听器。
 //! - [`WebSocketClient::on_open`] - 监听连接成功事件。
 //! - [`WebSocketClient::on_close`] - 监听连接断开事件。
 //! - [`WebSocketClient::on_error`] - 监听连接错误事件。
 //! - [`WebSocketClient::remove_listener`] - 移除事件监听器。
+//! - [`WebSocketClient::send`] - 通过已建立的连接发送文本消息。
+//! - [`WebSocketClient::is_open`] - 连接是否仍然有效。
 //! - [`WebSocketClient::disconnect`] - 断开连接。
 //!
 //! # 示例
@@ -72,18 +75,28 @@
 //! - 错误处理使用 `WebSocketError`，连接失败或操作错误。
 
 use futures_util::{SinkExt, StreamExt};
+use http::Uri;
+use hyper_util::client::legacy::connect::HttpConnector;
+use hyper_util::client::legacy::connect::proxy::{SocksV5, Tunnel};
+use hyper_util::client::proxy::matcher::Intercept;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::hash::Hash;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::net::TcpStream;
 use tokio::sync::Mutex;
-use tokio::time::sleep;
-use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
+use tokio::time::{Interval, MissedTickBehavior, interval, sleep};
+use tokio_tungstenite::{
+    MaybeTlsStream, WebSocketStream, client_async_tls, connect_async,
+    tungstenite::protocol::Message,
+};
 use tokio_util::sync::CancellationToken;
+use tower_service::Service;
 use url::Url;
 
 use crate::utils::error::Error;
+use crate::utils::{current_http_proxy_config, ws_scheme};
 
 /// WebSocket 错误类型
 #[derive(Debug, thiserror::Error)]
@@ -94,12 +107,92 @@ pub enum WebSocketError {
     Other(String),
 }
 
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// 建立 WebSocket 连接，配置了代理时通过代理隧道连接
+async fn open_stream(url: &str) -> Result<WsStream, WebSocketError> {
+    let target = Url::parse(url).map_err(|e| WebSocketError::ConnectionFailed(e.to_string()))?;
+    let proxy = current_http_proxy_config()
+        .intercept(&target)
+        .map_err(|e| WebSocketError::ConnectionFailed(e.to_string()))?;
+
+    let Some(proxy) = proxy else {
+        return connect_async(url)
+            .await
+            .map(|(stream, _)| stream)
+            .map_err(|e| WebSocketError::ConnectionFailed(e.to_string()));
+    };
+
+    let host = target
+        .host_str()
+        .ok_or_else(|| WebSocketError::ConnectionFailed(format!("URL 缺少主机: {}", url)))?;
+    let port = target.port_or_known_default().unwrap_or(443);
+    let tcp = proxy_tunnel(&proxy, host, port).await?;
+
+    client_async_tls(url, tcp)
+        .await
+        .map(|(stream, _)| stream)
+        .map_err(|e| WebSocketError::ConnectionFailed(e.to_string()))
+}
+
+/// 通过 HTTP CONNECT 或 SOCKS5 代理建立到目标主机的 TCP 隧道
+async fn proxy_tunnel(
+    proxy: &Intercept,
+    host: &str,
+    port: u16,
+) -> Result<TcpStream, WebSocketError> {
+    let failed = |e: String| WebSocketError::ConnectionFailed(format!("代理连接失败: {}", e));
+    let proxy_uri = proxy.uri();
+    let proxy_host = proxy_uri
+        .host()
+        .ok_or_else(|| failed(format!("代理地址缺少主机: {}", proxy_uri)))?;
+    let parse_uri = |raw: String| {
+        raw.parse::<Uri>()
+            .map_err(|e| failed(format!("{}: {}", raw, e)))
+    };
+    let dst = parse_uri(format!("http://{}:{}", host, port))?;
+
+    let stream = match proxy_uri.scheme_str() {
+        Some(scheme @ ("socks5" | "socks5h")) => {
+            let proxy_port = proxy_uri.port_u16().unwrap_or(1080);
+            let connect_to = parse_uri(format!("http://{}:{}", proxy_host, proxy_port))?;
+            let mut socks =
+                SocksV5::new(connect_to, HttpConnector::new()).local_dns(scheme == "socks5");
+            if let Some((username, password)) = proxy.raw_auth() {
+                socks = socks.with_auth(username.to_string(), password.to_string());
+            }
+            socks.call(dst).await.map_err(|e| failed(e.to_string()))?
+        }
+        Some("http") => {
+            let proxy_port = proxy_uri.port_u16().unwrap_or(80);
+            let connect_to = parse_uri(format!("http://{}:{}", proxy_host, proxy_port))?;
+            let mut tunnel = Tunnel::new(connect_to, HttpConnector::new());
+            if let Some(auth) = proxy.basic_auth() {
+                tunnel = tunnel.with_auth(auth.clone());
+            }
+            tunnel.call(dst).await.map_err(|e| failed(e.to_string()))?
+        }
+        scheme => {
+            return Err(failed(format!(
+                "不支持的代理协议: {}",
+                scheme.unwrap_or_default()
+            )));
+        }
+    };
+    Ok(stream.into_inner())
+}
+
 /// 基础 WebSocket 事件
 #[derive(Debug, Clone)]
 pub enum WsBaseEvent {
     Open,
     Close(Option<String>),
     Error(String),
+    /// 即将自动重连，`attempt` 为本次重连是第几次尝试，`delay` 为重连前的等待时间
+    Reconnecting {
+        attempt: u32,
+        delay: Duration,
+    },
 }
 
 /// WebSocket 事件类型枚举
@@ -108,6 +201,7 @@ pub enum WsEventType {
     Open,
     Close,
     Error,
+    Reconnecting,
     All,
 }
 
@@ -115,11 +209,22 @@ pub enum WsEventType {
 pub type EventListener = Arc<dyn Fn(WsBaseEvent) + Send + Sync + 'static>;
 pub type TypedListener<D> = Arc<dyn Fn(D) + Send + Sync + 'static>;
 pub type WsLogHook = Arc<dyn Fn(&str) + Send + Sync + 'static>;
+/// 连接（包括自动重连）成功钩子，参数表示是否为重连
+pub type WsOpenHook = Arc<dyn Fn(bool) + Send + Sync + 'static>;
+/// 重连前钩子，参数为 (第几次尝试, 重连前的等待时间)
+pub type WsReconnectHook = Arc<dyn Fn(u32, Duration) + Send + Sync + 'static>;
+/// 事件转换钩子，在派发前根据自身状态改写 (事件类型, 事件数据)，可展开为多个事件依次派发
+pub type EventTransform<E, D> = Arc<dyn Fn(E, D) -> Vec<(E, D)> + Send + Sync + 'static>;
 
 /// 自动重连策略
+///
+/// 连接意外断开后按指数退避自动重连，每次重连成功都会触发 `open` 事件；
+/// 连续失败达到 `max_attempts` 次后放弃，并派发最终的 `close` 事件。
 #[derive(Clone, Debug)]
 pub struct RetryPolicy {
-    /// 最大尝试次数（包含首次）
+    /// 是否在连接断开后自动重连，关闭后由调用方自行调用 `reconnect`
+    pub auto_reconnect: bool,
+    /// 最大尝试次数（包含首次），0 表示不限次数
     pub max_attempts: u32,
     /// 首次重试延迟
     pub initial_delay: Duration,
@@ -129,9 +234,20 @@ pub struct RetryPolicy {
     pub backoff_factor: f64,
 }
 
+impl RetryPolicy {
+    /// 不自动重连的策略，连接断开后直接派发 `close` 事件
+    pub fn disabled() -> Self {
+        Self {
+            auto_reconnect: false,
+            ..Self::default()
+        }
+    }
+}
+
 impl Default for RetryPolicy {
     fn default() -> Self {
         Self {
+            auto_reconnect: true,
             max_attempts: 3,
             initial_delay: Duration::from_millis(400),
             max_delay: Duration::from_secs(8),
@@ -140,6 +256,29 @@ impl Default for RetryPolicy {
     }
 }
 
+/// 默认心跳间隔，避免服务端断开长时间静默的连接
+pub const DEFAULT_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 创建心跳定时器，首次心跳在一个间隔之后发送
+fn heartbeat_ticker(heartbeat_interval: Option<Duration>) -> Option<Interval> {
+    heartbeat_interval.map(|period| {
+        let mut ticker = interval(period);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        ticker.reset();
+        ticker
+    })
+}
+
+/// 等待下一次心跳，未启用心跳时永不返回
+async fn next_heartbeat(ticker: &mut Option<Interval>) {
+    match ticker {
+        Some(ticker) => {
+            ticker.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
 /// 通用事件总线
 #[derive(Clone)]
 pub struct EventBus<E, D>
@@ -180,6 +319,31 @@ where
             .push(Arc::new(listener));
     }
 
+    /// 添加监听器并返回其句柄，可通过 [`EventBus::remove_listener_handle`] 单独移除
+    pub async fn add_listener_handle<F>(&self, event: E, listener: F) -> TypedListener<D>
+    where
+        F: Fn(D) + Send + Sync + 'static,
+    {
+        let listener: TypedListener<D> = Arc::new(listener);
+        let mut listeners = self.listeners.lock().await;
+        listeners
+            .entry(event)
+            .or_insert_with(Vec::new)
+            .push(listener.clone());
+        listener
+    }
+
+    /// 移除句柄对应的监听器，同一事件的其他监听器不受影响
+    pub async fn remove_listener_handle(&self, event: &E, handle: &TypedListener<D>) {
+        let mut listeners = self.listeners.lock().await;
+        if let Some(list) = listeners.get_mut(event) {
+            list.retain(|l| !Arc::ptr_eq(l, handle));
+            if list.is_empty() {
+                listeners.remove(event);
+            }
+        }
+    }
+
     pub async fn remove_listener(&self, event: Option<E>) {
         let mut listeners = self.listeners.lock().await;
         match event {
@@ -236,13 +400,16 @@ pub struct WebSocketClient {
 }
 
 /// 构造带查询参数的 WebSocket URL，自动进行 query 编码
+///
+/// 协议跟随全局服务地址：`https` 对应 `wss`，`http` 对应 `ws`
 pub fn build_ws_url(
     domain: &str,
     path: &str,
     params: &[(&str, String)],
 ) -> Result<String, WebSocketError> {
     let mut url = Url::parse(&format!(
-        "wss://{}/{}",
+        "{}://{}/{}",
+        ws_scheme(),
         domain,
         path.trim_start_matches('/')
     ))
@@ -261,15 +428,25 @@ pub fn build_ws_url(
 impl WebSocketClient {
     /// 创建并连接 WebSocket
     pub async fn connect<H>(url: &str, message_handler: H) -> Result<Self, WebSocketError>
+    where
+        H: MessageHandler + 'static,
+    {
+        Self::connect_with_heartbeat(url, message_handler, None).await
+    }
+
+    /// 创建并连接 WebSocket，按 `heartbeat_interval` 定时发送 Ping 帧保持连接
+    pub async fn connect_with_heartbeat<H>(
+        url: &str,
+        message_handler: H,
+        heartbeat_interval: Option<Duration>,
+    ) -> Result<Self, WebSocketError>
     where
         H: MessageHandler + 'static,
     {
         let listeners = EventBus::<WsEventType, WsBaseEvent>::new();
         let cancel_token = CancellationToken::new();
 
-        let (ws_stream, _) = connect_async(url)
-            .await
-            .map_err(|e| WebSocketError::ConnectionFailed(e.to_string()))?;
+        let ws_stream = open_stream(url).await?;
 
         let (mut write, mut read) = ws_stream.split();
         let (outbound_tx, mut outbound_rx) = tokio::sync::mpsc::unbounded_channel::<Message>();
@@ -287,15 +464,31 @@ impl WebSocketClient {
                         .emit(&WsEventType::Open, WsBaseEvent::Open, Some(&WsEventType::All))
                         .await;
 
+                    let mut heartbeat = heartbeat_ticker(heartbeat_interval);
+                    let disconnected_reason: Option<String>;
                     loop {
                         tokio::select! {
                             _ = cancel.cancelled() => {
-                                break;
+                                return;
+                            }
+                            _ = next_heartbeat(&mut heartbeat) => {
+                                if let Err(e) = write.send(Message::Ping(Vec::new().into())).await {
+                                    disconnected_reason = Some(e.to_string());
+                                    listeners_clone
+                                        .emit(
+                                            &WsEventType::Error,
+                                            WsBaseEvent::Error(e.to_string()),
+                                            Some(&WsEventType::All),
+                                        )
+                                        .await;
+                                    break;
+                                }
                             }
                             outbound = outbound_rx.recv() => {
                                 match outbound {
                                     Some(msg) => {
                                         if let Err(e) = write.send(msg).await {
+                                            disconnected_reason = Some(e.to_string());
                                             listeners_clone
                                                 .emit(
                                                     &WsEventType::Error,
@@ -306,7 +499,7 @@ impl WebSocketClient {
                                             break;
                                         }
                                     }
-                                    None => break,
+                                    None => return,
                                 }
                             }
                             incoming = read.next() => {
@@ -315,17 +508,12 @@ impl WebSocketClient {
                                         message_handler.handle_message(text.to_string());
                                     }
                                     Some(Ok(Message::Close(frame))) => {
-                                        let reason = frame.map(|f| f.reason.to_string());
-                                        listeners_clone
-                                            .emit(
-                                                &WsEventType::Close,
-                                                WsBaseEvent::Close(reason),
-                                                Some(&WsEventType::All),
-                                            )
-                                            .await;
+                                        disconnected_reason = frame.map(|f| f.reason.to_string());
                                         break;
                                     }
+                                    Some(Ok(_)) => {}
                                     Some(Err(e)) => {
+                                        disconnected_reason = Some(e.to_string());
                                         listeners_clone
                                             .emit(
                                                 &WsEventType::Error,
@@ -335,11 +523,23 @@ impl WebSocketClient {
                                             .await;
                                         break;
                                     }
-                                    _ => {}
+                                    None => {
+                                        disconnected_reason = Some("stream ended".to_string());
+                                        break;
+                                    }
                                 }
                             }
                         }
                     }
+
+                    // 连接因任何原因断开都派发 close 事件
+                    listeners_clone
+                        .emit(
+                            &WsEventType::Close,
+                            WsBaseEvent::Close(disconnected_reason),
+                            Some(&WsEventType::All),
+                        )
+                        .await;
                 } => {}
             }
         });
@@ -359,6 +559,33 @@ impl WebSocketClient {
         retry_policy: RetryPolicy,
         log_hook: Option<WsLogHook>,
     ) -> Result<Self, WebSocketError>
+    where
+        H: MessageHandler + Clone + 'static,
+    {
+        Self::connect_managed_with_hooks(
+            url,
+            message_handler,
+            retry_policy,
+            log_hook,
+            None,
+            None,
+            Some(DEFAULT_HEARTBEAT_INTERVAL),
+        )
+        .await
+    }
+
+    /// 创建带自动重连的 WebSocket 连接，每次连接成功后调用 `open_hook`，
+    /// 每次自动重连前派发 `reconnecting` 事件并调用 `reconnect_hook`，
+    /// 并按 `heartbeat_interval` 定时发送 Ping 帧保持连接。
+    pub async fn connect_managed_with_hooks<H>(
+        url: String,
+        message_handler: H,
+        retry_policy: RetryPolicy,
+        log_hook: Option<WsLogHook>,
+        open_hook: Option<WsOpenHook>,
+        reconnect_hook: Option<WsReconnectHook>,
+        heartbeat_interval: Option<Duration>,
+    ) -> Result<Self, WebSocketError>
     where
         H: MessageHandler + Clone + 'static,
     {
@@ -366,9 +593,7 @@ impl WebSocketClient {
         let cancel_token = CancellationToken::new();
         let (outbound_tx, mut outbound_rx) = tokio::sync::mpsc::unbounded_channel::<Message>();
 
-        let (initial_stream, _) = connect_async(&url)
-            .await
-            .map_err(|e| WebSocketError::ConnectionFailed(e.to_string()))?;
+        let initial_stream = open_stream(&url).await?;
 
         let listeners_for_initial = listeners.clone();
         let cancel = cancel_token.clone();
@@ -376,6 +601,7 @@ impl WebSocketClient {
             let mut attempt: u32 = 0;
             let mut delay = retry_policy.initial_delay;
             let mut pending_stream = Some(initial_stream);
+            let mut reconnecting = false;
 
             loop {
                 if cancel.is_cancelled() {
@@ -385,7 +611,7 @@ impl WebSocketClient {
                 let connect_result = if let Some(ws_stream) = pending_stream.take() {
                     Ok(ws_stream)
                 } else {
-                    connect_async(&url).await.map(|(stream, _)| stream)
+                    open_stream(&url).await
                 };
 
                 match connect_result {
@@ -399,15 +625,33 @@ impl WebSocketClient {
                                 Some(&WsEventType::All),
                             )
                             .await;
+                        if let Some(hook) = &open_hook {
+                            hook(reconnecting);
+                        }
+                        reconnecting = true;
 
                         let (mut write, mut read) = ws_stream.split();
                         let mut disconnected_reason: Option<String> = None;
+                        let mut heartbeat = heartbeat_ticker(heartbeat_interval);
 
                         loop {
                             tokio::select! {
                                 _ = cancel.cancelled() => {
                                     return;
                                 }
+                                _ = next_heartbeat(&mut heartbeat) => {
+                                    if let Err(e) = write.send(Message::Ping(Vec::new().into())).await {
+                                        disconnected_reason = Some(e.to_string());
+                                        listeners_for_initial
+                                            .emit(
+                                                &WsEventType::Error,
+                                                WsBaseEvent::Error(e.to_string()),
+                                                Some(&WsEventType::All),
+                                            )
+                                            .await;
+                                        break;
+                                    }
+                                }
                                 outbound = outbound_rx.recv() => {
                                     match outbound {
                                         Some(msg) => {
@@ -432,15 +676,12 @@ impl WebSocketClient {
                                             message_handler.handle_message(text.to_string());
                                         }
                                         Some(Ok(Message::Close(frame))) => {
-                                            let reason = frame.map(|f| f.reason.to_string());
-                                            disconnected_reason = reason.clone();
-                                            listeners_for_initial
-                                                .emit(
-                                                    &WsEventType::Close,
-                                                    WsBaseEvent::Close(reason),
-                                                    Some(&WsEventType::All),
-                                                )
-                                                .await;
+                                            disconnected_reason = Some(
+                                                frame
+                                                    .map(|f| f.reason.to_string())
+                                                    .filter(|reason| !reason.is_empty())
+                                                    .unwrap_or_else(|| "closed by server".to_string()),
+                                            );
                                             break;
                                         }
                                         Some(Ok(_)) => {}
@@ -455,26 +696,28 @@ impl WebSocketClient {
                                                 .await;
                                             break;
                                         }
-                                        None => {
-                                            listeners_for_initial
-                                                .emit(
-                                                    &WsEventType::Close,
-                                                    WsBaseEvent::Close(Some("stream ended".to_string())),
-                                                    Some(&WsEventType::All),
-                                                )
-                                                .await;
-                                            break;
-                                        }
+                                        None => break,
                                     }
                                 }
                             }
                         }
 
+                        let reason =
+                            disconnected_reason.unwrap_or_else(|| "stream ended".to_string());
                         if let Some(hook) = &log_hook {
-                            hook(&format!(
-                                "WebSocket disconnected: {}",
-                                disconnected_reason.unwrap_or_else(|| "stream ended".to_string())
-                            ));
+                            hook(&format!("WebSocket disconnected: {}", reason));
+                        }
+
+                        // 自动重连时断开只派发 `reconnecting`，放弃重连时才派发唯一一次 `close`
+                        if !retry_policy.auto_reconnect {
+                            listeners_for_initial
+                                .emit(
+                                    &WsEventType::Close,
+                                    WsBaseEvent::Close(Some(reason)),
+                                    Some(&WsEventType::All),
+                                )
+                                .await;
+                            return;
                         }
                     }
                     Err(err) => {
@@ -494,12 +737,38 @@ impl WebSocketClient {
                 }
 
                 if retry_policy.max_attempts > 0 && attempt >= retry_policy.max_attempts {
-                    attempt = 0;
+                    if let Some(hook) = &log_hook {
+                        hook(&format!(
+                            "WebSocket reconnect gave up after {} attempts",
+                            attempt
+                        ));
+                    }
+                    listeners_for_initial
+                        .emit(
+                            &WsEventType::Close,
+                            WsBaseEvent::Close(Some("reconnect attempts exhausted".to_string())),
+                            Some(&WsEventType::All),
+                        )
+                        .await;
+                    break;
                 }
 
                 if let Some(hook) = &log_hook {
                     hook(&format!("WebSocket reconnecting in {:?}", delay));
                 }
+                listeners_for_initial
+                    .emit(
+                        &WsEventType::Reconnecting,
+                        WsBaseEvent::Reconnecting {
+                            attempt: attempt + 1,
+                            delay,
+                        },
+                        Some(&WsEventType::All),
+                    )
+                    .await;
+                if let Some(hook) = &reconnect_hook {
+                    hook(attempt + 1, delay);
+                }
 
                 tokio::select! {
                     _ = cancel.cancelled() => break,
@@ -538,6 +807,8 @@ impl WebSocketClient {
     }
 
     /// 监听 close 事件
+    ///
+    /// 开启自动重连时，断开后的重连期间不派发，只在放弃重连时派发一次
     pub async fn on_close<F>(&self, listener: F)
     where
         F: Fn(Option<String>) + Send + Sync + 'static,
@@ -563,6 +834,19 @@ impl WebSocketClient {
         .await;
     }
 
+    /// 监听 reconnecting 事件，参数为 (第几次尝试, 重连前的等待时间)
+    pub async fn on_reconnecting<F>(&self, listener: F)
+    where
+        F: Fn(u32, Duration) + Send + Sync + 'static,
+    {
+        self.add_listener(WsEventType::Reconnecting, move |event| {
+            if let WsBaseEvent::Reconnecting { attempt, delay } = event {
+                listener(attempt, delay);
+            }
+        })
+        .await;
+    }
+
     /// 移除监听器
     pub async fn remove_listener(&self, event: Option<WsEventType>) {
         self.listeners.remove_listener(event).await;
@@ -575,10 +859,27 @@ impl WebSocketClient {
 
     /// 发送文本消息
     pub fn send_text(&self, text: &str) -> Result<(), WebSocketError> {
+        self.send(text.to_string())
+    }
+
+    /// 通过已建立的连接发送文本帧
+    ///
+    /// 连接已断开或已取消时返回 [`WebSocketError`]。
+    pub fn send(&self, msg: String) -> Result<(), WebSocketError> {
+        if self.cancel_token.is_cancelled() || self.outbound_tx.is_closed() {
+            return Err(WebSocketError::Other(
+                "websocket is already closed".to_string(),
+            ));
+        }
         self.outbound_tx
-            .send(Message::Text(text.to_string().into()))
+            .send(Message::Text(msg.into()))
             .map_err(|e| WebSocketError::Other(format!("send message failed: {}", e)))
     }
+
+    /// 连接是否仍然有效（未取消且后台任务仍在运行）
+    pub fn is_open(&self) -> bool {
+        !self.cancel_token.is_cancelled() && !self.outbound_tx.is_closed()
+    }
 }
 
 impl Drop for WebSocketClient {
@@ -593,6 +894,9 @@ pub struct WsConnection {
     client: Option<WebSocketClient>,
     retry_policy: RetryPolicy,
     log_hook: Option<WsLogHook>,
+    open_hook: Option<WsOpenHook>,
+    reconnect_hook: Option<WsReconnectHook>,
+    heartbeat_interval: Option<Duration>,
 }
 
 impl WsConnection {
@@ -601,11 +905,14 @@ impl WsConnection {
             client: None,
             retry_policy: RetryPolicy::default(),
             log_hook: None,
+            open_hook: None,
+            reconnect_hook: None,
+            heartbeat_interval: Some(DEFAULT_HEARTBEAT_INTERVAL),
         }
     }
 
     pub fn is_connected(&self) -> bool {
-        self.client.is_some()
+        self.client.as_ref().is_some_and(WebSocketClient::is_open)
     }
 
     pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
@@ -623,6 +930,28 @@ impl WsConnection {
         self.log_hook = Some(hook);
     }
 
+    /// 设置心跳间隔，`None` 表示不发送心跳，下次连接时生效
+    pub fn set_heartbeat_interval(&mut self, heartbeat_interval: Option<Duration>) {
+        self.heartbeat_interval = heartbeat_interval;
+    }
+
+    /// 设置连接成功钩子，自动重连成功后同样会调用
+    pub fn set_open_hook<F>(&mut self, hook: F)
+    where
+        F: Fn(bool) + Send + Sync + 'static,
+    {
+        self.open_hook = Some(Arc::new(hook));
+    }
+
+    /// 设置重连前钩子，自动重连与 [`WsConnection::reconnect`] 的每次重试前都会调用，
+    /// 参数为 (第几次尝试, 重连前的等待时间)
+    pub fn set_reconnect_hook<F>(&mut self, hook: F)
+    where
+        F: Fn(u32, Duration) + Send + Sync + 'static,
+    {
+        self.reconnect_hook = Some(Arc::new(hook));
+    }
+
     fn log(&self, message: &str) {
         if let Some(hook) = &self.log_hook {
             hook(message);
@@ -639,17 +968,20 @@ impl WsConnection {
         H: MessageHandler + Clone + 'static,
     {
         if self.client.is_some() {
-            if !reload {
+            if !reload && self.is_connected() {
                 return Ok(());
             }
             self.disconnect();
         }
 
-        let ws = WebSocketClient::connect_managed(
+        let ws = WebSocketClient::connect_managed_with_hooks(
             url.to_string(),
             message_handler,
             self.retry_policy.clone(),
             self.log_hook.clone(),
+            self.open_hook.clone(),
+            self.reconnect_hook.clone(),
+            self.heartbeat_interval,
         )
         .await?;
         self.client = Some(ws);
@@ -671,11 +1003,14 @@ impl WsConnection {
         let mut last_err: Option<WebSocketError> = None;
 
         for attempt in 1..=attempts {
-            match WebSocketClient::connect_managed(
+            match WebSocketClient::connect_managed_with_hooks(
                 url.to_string(),
                 message_handler.clone(),
                 self.retry_policy.clone(),
                 self.log_hook.clone(),
+                self.open_hook.clone(),
+                self.reconnect_hook.clone(),
+                self.heartbeat_interval,
             )
             .await
             {
@@ -697,6 +1032,9 @@ impl WsConnection {
                         "WebSocket reconnect attempt {}/{} failed, retrying in {:?}",
                         attempt, attempts, delay
                     ));
+                    if let Some(hook) = &self.reconnect_hook {
+                        hook(attempt + 1, delay);
+                    }
                     sleep(delay).await;
 
                     let next = (delay.as_secs_f64() * self.retry_policy.backoff_factor)
@@ -737,6 +1075,7 @@ where
     emitter: EventBus<E, D>,
     log_hook: Option<WsLogHook>,
     parser: fn(&Value) -> Result<(E, D), Error>,
+    transform: Option<EventTransform<E, D>>,
     all_event: Option<E>,
     error_context: &'static str,
 }
@@ -755,15 +1094,36 @@ where
             emitter: EventBus::new(),
             log_hook: None,
             parser,
+            transform: None,
             all_event,
             error_context,
         }
     }
 
+    /// 设置事件转换钩子，解析成功后、派发前调用
+    pub fn set_transform<F>(&mut self, transform: F)
+    where
+        F: Fn(E, D) -> Vec<(E, D)> + Send + Sync + 'static,
+    {
+        self.transform = Some(Arc::new(transform));
+    }
+
     pub fn get_emitter(&self) -> EventBus<E, D> {
         self.emitter.clone()
     }
 
+    /// 直接派发已解析的事件，与收到的消息一样经过事件转换钩子
+    pub async fn dispatch(&self, event_type: E, event: D) {
+        dispatch_events(
+            &self.emitter,
+            self.transform.as_ref(),
+            self.all_event.as_ref(),
+            event_type,
+            event,
+        )
+        .await;
+    }
+
     pub fn set_log_hook_arc(&mut self, hook: WsLogHook) {
         self.log_hook = Some(hook);
     }
@@ -779,13 +1139,21 @@ where
             let emitter = self.get_emitter();
             let log_hook = self.log_hook.clone();
             let parser = self.parser;
+            let transform = self.transform.clone();
             let all_event = self.all_event.clone();
             let context = self.error_context;
 
             tokio::spawn(async move {
                 match parser(&json) {
                     Ok((event_type, event)) => {
-                        emitter.emit(&event_type, event, all_event.as_ref()).await;
+                        dispatch_events(
+                            &emitter,
+                            transform.as_ref(),
+                            all_event.as_ref(),
+                            event_type,
+                            event,
+                        )
+                        .await;
                     }
                     Err(e) => {
                         if let Some(hook) = log_hook {
@@ -798,21 +1166,121 @@ where
     }
 }
 
+/// 经过事件转换钩子后派发事件
+async fn dispatch_events<E, D>(
+    emitter: &EventBus<E, D>,
+    transform: Option<&EventTransform<E, D>>,
+    all_event: Option<&E>,
+    event_type: E,
+    event: D,
+) where
+    E: Eq + Hash + Clone + Send + Sync + 'static,
+    D: Clone + Send + 'static,
+{
+    let events = match transform {
+        Some(transform) => transform(event_type, event),
+        None => vec![(event_type, event)],
+    };
+    for (event_type, event) in events {
+        emitter.emit(&event_type, event, all_event).await;
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{EventBus, RetryPolicy, WsEventType, build_ws_url};
+    use super::{
+        EventBus, MessageHandler, RetryPolicy, WebSocketClient, WsEventType, build_ws_url,
+        proxy_tunnel,
+    };
+    use crate::utils::HttpProxyConfig;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
     use tokio::sync::mpsc;
     use tokio::time::{Duration, timeout};
 
+    #[tokio::test]
+    async fn http_proxy_tunnel_sends_decoded_credentials() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut conn, _) = listener.accept().await.unwrap();
+            let mut head = Vec::new();
+            let mut byte = [0u8; 1];
+            while !head.ends_with(b"\r\n\r\n") {
+                conn.read_exact(&mut byte).await.unwrap();
+                head.push(byte[0]);
+            }
+            conn.write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+                .await
+                .unwrap();
+            String::from_utf8(head).unwrap()
+        });
+
+        let target = url::Url::parse("wss://fishpi.cn/chat-room-channel").unwrap();
+        let proxy = HttpProxyConfig::custom(&format!("http://user:p%40ss@{}", addr))
+            .intercept(&target)
+            .unwrap()
+            .unwrap();
+        proxy_tunnel(&proxy, "fishpi.cn", 443).await.unwrap();
+
+        let head = server.await.unwrap().to_lowercase();
+        assert!(head.starts_with("connect fishpi.cn:443 http/1.1"));
+        // "user:p@ss" 的 Base64
+        assert!(head.contains("proxy-authorization: basic dxnlcjpwqhnz"));
+    }
+
     #[test]
     fn retry_policy_defaults_are_reasonable() {
         let p = RetryPolicy::default();
+        assert!(p.auto_reconnect);
         assert_eq!(p.max_attempts, 3);
         assert_eq!(p.initial_delay, Duration::from_millis(400));
         assert_eq!(p.max_delay, Duration::from_secs(8));
         assert!((p.backoff_factor - 2.0).abs() < f64::EPSILON);
     }
 
+    #[derive(Clone)]
+    struct IgnoreMessages;
+
+    impl MessageHandler for IgnoreMessages {
+        fn handle_message(&self, _msg: String) {}
+    }
+
+    #[tokio::test]
+    async fn managed_connection_closes_once_after_giving_up() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}/", listener.local_addr().unwrap());
+        // 只接受一次连接，关闭后监听器随任务结束，之后的重连都会失败
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let _ = ws.close(None).await;
+        });
+
+        let policy = RetryPolicy {
+            max_attempts: 1,
+            initial_delay: Duration::from_millis(20),
+            max_delay: Duration::from_millis(20),
+            ..RetryPolicy::default()
+        };
+        let ws = WebSocketClient::connect_managed(url, IgnoreMessages, policy, None)
+            .await
+            .expect("connect");
+        let (tx, mut rx) = mpsc::unbounded_channel::<Option<String>>();
+        ws.on_close(move |reason| {
+            let _ = tx.send(reason);
+        })
+        .await;
+
+        let reason = timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .expect("close timeout")
+            .expect("close missing");
+        assert_eq!(reason.as_deref(), Some("reconnect attempts exhausted"));
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(rx.try_recv().is_err());
+    }
+
     #[tokio::test]
     async fn event_bus_emits_target_and_all() {
         let bus = EventBus::<WsEventType, String>::new();
@@ -851,6 +1319,36 @@ mod tests {
         assert!(got.iter().any(|s| s == "all:hello"));
     }
 
+    #[tokio::test]
+    async fn event_bus_removes_single_listener_handle() {
+        let bus = EventBus::<WsEventType, String>::new();
+        let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+
+        let tx1 = tx.clone();
+        let handle = bus
+            .add_listener_handle(WsEventType::Open, move |msg| {
+                let _ = tx1.send(format!("removed:{msg}"));
+            })
+            .await;
+        bus.add_listener(WsEventType::Open, move |msg| {
+            let _ = tx.send(format!("kept:{msg}"));
+        })
+        .await;
+
+        bus.remove_listener_handle(&WsEventType::Open, &handle)
+            .await;
+        bus.emit(&WsEventType::Open, "hello".to_string(), None)
+            .await;
+
+        let got = timeout(Duration::from_secs(1), rx.recv())
+            .await
+            .expect("recv timeout")
+            .expect("message missing");
+        assert_eq!(got, "kept:hello");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(rx.try_recv().is_err());
+    }
+
     #[test]
     fn build_ws_url_encodes_query_params() {
         let url = build_ws_url(
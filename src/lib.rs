@@ -5,6 +5,7 @@
 //! ## 主要组件
 //!
 //! - [`FishPi`] - 静态客户端，提供不需要认证的操作（如登录、注册、验证）。
+//! - [`FishPi::finger`] - 金手指管理客户端，使用独立的金手指 key 认证。
 //! - [`api`] - API 客户端模块，包含各个子模块（如用户、文章等）。
 //! - [`model`] - 数据模型模块，定义请求和响应的数据结构。
 //! - [`utils`] - 工具模块，提供 HTTP 请求、错误处理等辅助功能。
@@ -15,6 +16,8 @@
 //! - **类型安全**: 使用 Serde 进行序列化/反序列化，确保数据类型安全。
 //! - **错误处理**: 统一的错误类型和处理机制。
 //! - **文件上传**: 支持多文件上传。
+//! - **自定义服务地址**: 通过 [`FishPi::builder`] 或 [`FishPi::new_with_config`] 连接自建或测试环境。
+//! - **会话持久化**: 将 API key 保存到本地文件，重启后免登录恢复。
 //!
 //! ## 示例
 //!
@@ -34,26 +37,65 @@ pub mod api;
 pub mod model;
 pub mod utils;
 
+use std::path::Path;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
 use serde_json::{Value, json};
+use url::Url;
 
 use crate::{
-    api::user::User,
+    api::{finger::Finger, session::SavedSession, user::User},
     model::{
-        misc::{Log, LoginData, PreRegisterInfo, RegisterInfo, UserLite, UserVipInfo},
-        user::AtUser,
+        misc::{Log, LogType, LoginData, PreRegisterInfo, RegisterInfo, UserLite, UserVipInfo},
+        user::{AtUser, UserId},
+    },
+    utils::{
+        FishPiConfig, HttpProxyConfig, HttpRetryPolicy, ResponseResult, as_array_or_empty,
+        build_http_path, configure, configure_http_proxy, error::Error, full_url, get, get_bytes,
+        post, server_date,
     },
-    utils::{ResponseResult, build_http_path, error::Error, get, post},
 };
 
 /// 摸鱼派 Rust SDK 接口
 pub struct FishPi;
 
 impl FishPi {
+    /// 使用指定服务地址创建客户端
+    ///
+    /// 服务地址为进程级全局配置，之后所有 HTTP 请求与新建立的 WebSocket 连接都会使用该地址，
+    /// 不调用时默认为 `fishpi.cn`。
+    ///
+    /// - `config` 服务地址配置
+    ///
+    /// 返回客户端实例，按新配置重建 HTTP 客户端失败时返回错误
+    pub fn new_with_config(config: FishPiConfig) -> Result<FishPi, Error> {
+        configure(config)?;
+        Ok(FishPi)
+    }
+
+    /// 创建客户端构建器
+    pub fn builder() -> FishPiBuilder {
+        FishPiBuilder::default()
+    }
+
+    /// 创建金手指管理客户端
+    ///
+    /// 金手指接口使用管理员发放的金手指 key 认证，与用户登录得到的 API key 不同。
+    ///
+    /// - `gold_finger_key` 金手指 key
+    ///
+    /// 返回金手指客户端 [Finger]
+    pub fn finger(&self, gold_finger_key: &str) -> Finger {
+        Finger::new(gold_finger_key.to_string())
+    }
+
     /// 登录
     ///
     /// - `data` 登录账密
     ///
-    /// 返回用户实例
+    /// 返回用户实例；账号开启了两步验证且未填写或填错一次性密码时返回 [Error::MfaRequired]，
+    /// 可提示用户输入 [`LoginData::mfa_code`] 后重试
     pub async fn login(data: &LoginData) -> Result<User, Error> {
         let url = "api/getKey".to_string();
 
@@ -62,9 +104,7 @@ impl FishPi {
         let rsp = post(&url, Some(data_json)).await?;
 
         if rsp.get("code").and_then(|c| c.as_i64()).unwrap_or(-1) != 0 {
-            return Err(Error::Api(
-                rsp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+            return Err(login_error(&rsp));
         }
 
         let token = rsp["Key"].as_str().unwrap_or("").trim().to_string();
@@ -72,6 +112,24 @@ impl FishPi {
         Ok(User::new(token))
     }
 
+    /// 图形验证码地址，可直接在网页或图片控件中展示
+    ///
+    /// 返回验证码图片的完整 URL
+    pub fn captcha_url() -> String {
+        full_url("captcha")
+    }
+
+    /// 获取图形验证码图片，用于 [`FishPi::pre_register`] 的 `captcha` 字段
+    ///
+    /// 验证码保存在服务端会话中，服务端通过获取验证码时下发的会话 Cookie 校验。
+    /// SDK 会自动保存并携带该 Cookie（见 [`utils::cookies`]），预注册前不要调用
+    /// [`utils::clear_cookies`] 或切换服务地址。
+    ///
+    /// 返回验证码图片的原始字节（通常为 PNG）
+    pub async fn get_captcha() -> Result<Vec<u8>, Error> {
+        get_bytes("captcha").await
+    }
+
     /// 预注册
     ///
     /// - `data` 预注册数据
@@ -93,18 +151,16 @@ impl FishPi {
     /// - `code` 验证码
     ///
     /// 返回用户 ID
-    pub async fn verify(code: &str) -> Result<String, Error> {
+    pub async fn verify(code: &str) -> Result<UserId, Error> {
         let url = build_http_path("verify", &[("code", code.to_string())]);
 
         let rsp = get(&url).await?;
 
         if rsp.get("code").and_then(|c| c.as_i64()).unwrap_or(-1) != 0 {
-            return Err(Error::Api(
-                rsp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+            return Err(Error::from_response(&rsp, "API error"));
         }
 
-        Ok(rsp["userId"].as_str().unwrap_or("").to_string())
+        Ok(UserId::from_value(&rsp["userId"]))
     }
 
     /// 注册
@@ -127,9 +183,7 @@ impl FishPi {
         if let Some(code) = rsp.get("code").and_then(|c| c.as_i64())
             && code != 0
         {
-            return Err(Error::Api(
-                rsp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+            return Err(Error::from_response(&rsp, "API error"));
         }
 
         ResponseResult::from_value(&rsp)
@@ -150,14 +204,10 @@ impl FishPi {
         let rsp = post(&url, Some(data_json)).await?;
 
         if rsp.get("code").and_then(|c| c.as_i64()).unwrap_or(0) != 0 {
-            return Err(Error::Api(
-                rsp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+            return Err(Error::from_response(&rsp, "API error"));
         }
 
-        let at_users = rsp["data"]
-            .as_array()
-            .ok_or_else(|| Error::Api("Data is not an array".to_string()))?
+        let at_users = as_array_or_empty(&rsp["data"])?
             .iter()
             .map(AtUser::from_value)
             .collect::<Result<Vec<AtUser>, _>>()?;
@@ -173,9 +223,7 @@ impl FishPi {
 
         let rsp = get(&url).await?;
 
-        let user_lites = rsp["data"]
-            .as_array()
-            .ok_or_else(|| Error::Api("Data is not an array".to_string()))?
+        let user_lites = as_array_or_empty(&rsp["data"])?
             .iter()
             .map(UserLite::from_value)
             .collect::<Result<Vec<UserLite>, _>>()?;
@@ -194,14 +242,12 @@ impl FishPi {
         let rsp = get(&url).await?;
 
         if rsp.get("code").and_then(|c| c.as_i64()).unwrap_or(0) != 0 {
-            return Err(Error::Api(
-                rsp["msg"].as_str().unwrap_or("API error").to_string(),
-            ));
+            return Err(Error::from_response(&rsp, "API error"));
         }
 
         let data_obj = rsp["data"]
             .as_object()
-            .ok_or_else(|| Error::Api("Data is not an object".to_string()))?;
+            .ok_or_else(|| Error::api("Data is not an object".to_string()))?;
 
         let config_json_str = data_obj
             .get("configJson")
@@ -262,13 +308,353 @@ impl FishPi {
 
         let rsp = get(&url).await?;
 
-        let logs = rsp["data"]
-            .as_array()
-            .ok_or_else(|| Error::Api("Data is not an array".to_string()))?
+        let logs = as_array_or_empty(&rsp["data"])?
             .iter()
             .map(Log::from_value)
             .collect::<Result<Vec<Log>, _>>()?;
 
         Ok(logs)
     }
+
+    /// 按类型获取操作日志
+    ///
+    /// 服务端不支持按类型查询，这里获取指定页后在本地过滤，因此返回条数可能少于 `page_size`。
+    ///
+    /// - `type_` 日志类型
+    /// - `page` 页码
+    /// - `page_size` 每页数量
+    ///
+    /// 返回日志列表
+    pub async fn log_filtered(
+        type_: &LogType,
+        page: u32,
+        page_size: u32,
+    ) -> Result<Vec<Log>, Error> {
+        let logs = Self::log(page, page_size).await?;
+
+        Ok(logs
+            .into_iter()
+            .filter(|log| log.log_type() == *type_)
+            .collect())
+    }
+
+    /// 保存登录会话到本地 JSON 文件
+    ///
+    /// 文件中保存明文 API key 与已缓存的用户身份。Unix 下文件权限设为 `0600`，
+    /// 其他平台请自行确保文件不被他人读取，也不要将其提交到版本库。
+    ///
+    /// - `user` 用户实例
+    /// - `path` 文件路径
+    pub async fn save_session(user: &User, path: impl AsRef<Path>) -> Result<(), Error> {
+        let saved = SavedSession {
+            api_key: user.get_token(),
+            profile: user.session().cached_profile(),
+        };
+        let content = serde_json::to_string_pretty(&saved.to_value()?)
+            .map_err(|e| Error::Parse(format!("Failed to serialize SavedSession: {}", e)))?;
+
+        let mut options = tokio::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+
+        let mut file = options.open(path).await?;
+        // `mode` 只对新建文件生效，已存在的文件需要单独收紧权限
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(std::fs::Permissions::from_mode(0o600))
+                .await?;
+        }
+        tokio::io::AsyncWriteExt::write_all(&mut file, content.as_bytes()).await?;
+        Ok(())
+    }
+
+    /// 从本地 JSON 文件读取登录会话
+    ///
+    /// 读取后可使用 [`User::from_saved`] 创建用户实例。
+    ///
+    /// - `path` 由 [`FishPi::save_session`] 保存的文件路径
+    ///
+    /// 返回已保存的会话，API key 格式不合法时返回 [Error::InvalidApiKey]
+    pub async fn load_session(path: impl AsRef<Path>) -> Result<SavedSession, Error> {
+        let content = tokio::fs::read_to_string(path).await?;
+        let data: Value = serde_json::from_str(&content)
+            .map_err(|e| Error::Parse(format!("Failed to parse SavedSession: {}", e)))?;
+        let mut saved = SavedSession::from_value(&data)?;
+
+        saved.api_key = saved.api_key.trim().to_string();
+        FishPiBuilder::check_key_format(&saved.api_key)?;
+        Ok(saved)
+    }
+
+    /// 获取服务器当前时间
+    ///
+    /// 读取服务端响应的 `Date` 头，同时记录本地与服务器的时间偏移，
+    /// 之后可通过 [`utils::server_now`] 获取校正后的时间。
+    ///
+    /// 返回服务器时间
+    pub async fn server_time() -> Result<DateTime<Utc>, Error> {
+        server_date().await
+    }
+}
+
+/// API key 最小长度
+const MIN_KEY_LEN: usize = 16;
+
+/// [`FishPi`] 构建器，链式设置服务地址、超时、重试与代理后统一校验并生效
+///
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use fishpi_sdk::FishPi;
+///
+/// # fn main() -> Result<(), fishpi_sdk::utils::error::Error> {
+/// let user = FishPi::builder()
+///     .base_url("https://fishpi.cn")
+///     .timeout(Duration::from_secs(10))
+///     .api_key("your_api_key")
+///     .build_user()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct FishPiBuilder {
+    api_key: Option<String>,
+    base_url: Option<String>,
+    ws_base: Option<String>,
+    timeout: Option<Duration>,
+    retry: Option<HttpRetryPolicy>,
+    proxy: Option<HttpProxyConfig>,
+}
+
+impl FishPiBuilder {
+    /// 设置 API key，用于 [`FishPiBuilder::build_user`]
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// 设置 HTTP 接口根地址，如 `https://fishpi.cn`
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    /// 设置 WebSocket 域名，未设置时取 `base_url` 的主机
+    pub fn ws_base(mut self, ws_base: impl Into<String>) -> Self {
+        self.ws_base = Some(ws_base.into());
+        self
+    }
+
+    /// 设置 HTTP 请求默认超时时间
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// 设置 HTTP 请求重试策略
+    pub fn retry(mut self, retry: HttpRetryPolicy) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// 设置代理
+    pub fn proxy(mut self, proxy: HttpProxyConfig) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// 校验各项设置并合成服务地址配置，不修改全局状态
+    pub fn config(&self) -> Result<FishPiConfig, Error> {
+        let mut config = FishPiConfig::default();
+
+        if let Some(base_url) = &self.base_url {
+            let base_url = base_url.trim().trim_end_matches('/');
+            let parsed = Url::parse(base_url)
+                .map_err(|e| Error::api(format!("Invalid base url {}: {}", base_url, e)))?;
+            if !matches!(parsed.scheme(), "http" | "https") {
+                return Err(Error::api(format!(
+                    "Base url must use http or https: {}",
+                    base_url
+                )));
+            }
+            let host = parsed
+                .host_str()
+                .ok_or_else(|| Error::api(format!("Base url has no host: {}", base_url)))?;
+            config.ws_scheme = match parsed.scheme() {
+                "http" => "ws",
+                _ => "wss",
+            }
+            .to_string();
+            config.ws_base = match parsed.port() {
+                Some(port) => format!("{}:{}", host, port),
+                None => host.to_string(),
+            };
+            config.base_url = base_url.to_string();
+        }
+        if let Some(ws_base) = &self.ws_base {
+            let ws_base = ws_base.trim().trim_end_matches('/');
+            if ws_base.is_empty() || ws_base.contains("://") {
+                return Err(Error::api(format!(
+                    "WebSocket base must be a bare host: {}",
+                    ws_base
+                )));
+            }
+            config.ws_base = ws_base.to_string();
+        }
+        if let Some(timeout) = self.timeout {
+            if timeout.is_zero() {
+                return Err(Error::api("Timeout must be greater than zero"));
+            }
+            config.timeout = timeout;
+        }
+        if let Some(retry) = &self.retry {
+            config.retry = retry.clone();
+        }
+
+        Ok(config)
+    }
+
+    /// 校验并应用配置
+    ///
+    /// 返回客户端实例
+    pub fn build(self) -> Result<FishPi, Error> {
+        let config = self.config()?;
+        if let Some(proxy) = self.proxy {
+            configure_http_proxy(proxy)?;
+        }
+        FishPi::new_with_config(config)
+    }
+
+    /// 校验并应用配置，使用设置的 API key 创建用户客户端
+    ///
+    /// 返回用户实例，未设置 API key 或格式不合法时返回 [Error::InvalidApiKey]
+    pub fn build_user(mut self) -> Result<User, Error> {
+        let api_key = self
+            .api_key
+            .take()
+            .map(|key| key.trim().to_string())
+            .ok_or_else(|| Error::InvalidApiKey("API key is not set".to_string()))?;
+        Self::check_key_format(&api_key)?;
+        self.build()?;
+        Ok(User::new(api_key))
+    }
+
+    /// 离线校验 API key 格式，不发起网络请求
+    ///
+    /// API key 必须非空，且只包含字母、数字、`-` 或 `_`。
+    ///
+    /// - `api_key` API key
+    ///
+    /// 格式不合法时返回 [Error::InvalidApiKey]
+    pub fn check_key_format(api_key: &str) -> Result<(), Error> {
+        if api_key.is_empty() {
+            return Err(Error::InvalidApiKey("API key is empty".to_string()));
+        }
+        if let Some(c) = api_key
+            .chars()
+            .find(|c| !(c.is_ascii_alphanumeric() || *c == '-' || *c == '_'))
+        {
+            return Err(Error::InvalidApiKey(format!(
+                "API key contains invalid character {:?}",
+                c
+            )));
+        }
+        if api_key.len() < MIN_KEY_LEN {
+            return Err(Error::InvalidApiKey(format!(
+                "API key is too short ({} < {})",
+                api_key.len(),
+                MIN_KEY_LEN
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// 两步验证相关登录失败提示中的关键字
+///
+/// 登录接口在需要两步验证、未填写或填错一次性密码时，与密码错误一样只返回 `code: -1`，
+/// 没有可区分的错误码或其它字段，只能按错误信息识别，因此匹配所有两步验证提示共有的关键字，
+/// 而不是某一条完整提示。
+const MFA_MSG_MARKERS: &[&str] = &["一次性密码", "两步验证失败", "需要两步验证", "两步验证码"];
+
+/// 登录失败的错误，需要两步验证时返回 [Error::MfaRequired]
+fn login_error(rsp: &Value) -> Error {
+    let msg = rsp["msg"].as_str().unwrap_or_default();
+    if MFA_MSG_MARKERS.iter().any(|marker| msg.contains(marker)) {
+        return Error::MfaRequired(msg.to_string());
+    }
+    Error::from_response(rsp, "API error")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FishPi, FishPiBuilder, User, login_error};
+    use crate::utils::error::Error;
+    use serde_json::json;
+    use std::time::Duration;
+
+    #[test]
+    fn builder_validates_settings() {
+        let config = FishPi::builder()
+            .base_url("http://localhost:8080/")
+            .timeout(Duration::from_secs(3))
+            .config()
+            .expect("should be valid");
+        assert_eq!(config.base_url, "http://localhost:8080");
+        assert_eq!(config.ws_base, "localhost:8080");
+        assert_eq!(config.ws_scheme, "ws");
+        assert_eq!(config.timeout, Duration::from_secs(3));
+
+        assert!(FishPi::builder().base_url("fishpi.cn").config().is_err());
+        assert!(FishPi::builder().timeout(Duration::ZERO).config().is_err());
+        assert!(FishPi::builder().api_key("bad key").build_user().is_err());
+    }
+
+    #[test]
+    fn check_key_format_rejects_malformed_keys() {
+        let err = FishPiBuilder::check_key_format("").unwrap_err();
+        assert!(matches!(err, Error::InvalidApiKey(_)));
+        assert!(FishPiBuilder::check_key_format("short").is_err());
+        assert!(FishPiBuilder::check_key_format("abcdef0123456789 xyz").is_err());
+        assert!(FishPiBuilder::check_key_format("AbCdEf0123456789xyz").is_ok());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn save_session_tightens_existing_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!("fishpi-session-{}.json", std::process::id()));
+        std::fs::write(&path, "{}").expect("create file");
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).expect("chmod");
+
+        let user = User::new("AbCdEf0123456789xyz".to_string());
+        FishPi::save_session(&user, &path).await.expect("save");
+        let mode = std::fs::metadata(&path)
+            .expect("metadata")
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        let saved = FishPi::load_session(&path).await.expect("load");
+        assert_eq!(User::from_saved(saved).get_token(), "AbCdEf0123456789xyz");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn login_error_detects_mfa() {
+        let err =
+            login_error(&json!({ "code": -1, "msg": "两步验证失败，请填写正确的一次性密码" }));
+        assert!(matches!(err, Error::MfaRequired(_)));
+        let err = login_error(&json!({ "code": -1, "msg": "请输入两步验证码" }));
+        assert!(matches!(err, Error::MfaRequired(_)));
+        let err = login_error(&json!({ "code": -1, "msg": "需要两步验证" }));
+        assert!(matches!(err, Error::MfaRequired(_)));
+        let err = login_error(&json!({ "code": -1, "msg": "密码错误" }));
+        assert_eq!(err.code(), Some(-1));
+        // 只提到两步验证、但不是验证失败的错误不视为需要两步验证
+        let err = login_error(&json!({ "code": -1, "msg": "两步验证已关闭，密码错误" }));
+        assert!(matches!(err, Error::Api { .. }));
+    }
 }
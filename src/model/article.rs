@@ -1,10 +1,11 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
 
 use crate::impl_str_enum;
 use crate::model::user::Metal;
 use crate::model::{bool_from_int, bool_from_zero, deserialize_sys_metal};
-use crate::utils::error::Error;
+use crate::utils::{as_array_or_empty, error::Error, parse_time};
 
 fn normalize_float_numbers(value: &mut Value) {
     match value {
@@ -91,6 +92,48 @@ pub struct ArticlePost {
     /// 提问悬赏积分
     #[serde(rename = "articleQnAOfferPoint")]
     pub offerPoint: Option<u32>,
+    /// 编辑器类型，决定 `content` 按 Markdown 还是富文本渲染
+    #[serde(rename = "articleEditorType", default)]
+    pub editorType: EditorType,
+}
+
+/// 文章编辑器类型
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum EditorType {
+    /// Markdown 编辑器
+    #[default]
+    Markdown = 0,
+    /// 富文本（HTML）编辑器
+    RichText = 1,
+}
+
+impl EditorType {
+    pub fn from_index(index: u64) -> Self {
+        match index {
+            1 => EditorType::RichText,
+            _ => EditorType::Markdown,
+        }
+    }
+}
+
+impl Serialize for EditorType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_u8(self.clone() as u8)
+    }
+}
+
+impl<'de> Deserialize<'de> for EditorType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value: u64 = Deserialize::deserialize(deserializer)?;
+        Ok(EditorType::from_index(value))
+    }
 }
 
 impl ArticlePost {
@@ -103,6 +146,127 @@ impl ArticlePost {
         serde_json::to_value(self)
             .map_err(|e| Error::Parse(format!("Failed to serialize ArticlePost: {}", e)))
     }
+
+    /// 由文章详情构造发帖信息，内容取 Markdown 源文本
+    ///
+    /// - `detail` 文章详情
+    ///
+    /// 返回发帖信息，详情中没有 Markdown 源文本时返回 [Error::Parse]，
+    /// 不会把渲染后的 HTML 当作源文本提交覆盖文章
+    pub fn from_detail(detail: &ArticleDetail) -> Result<Self, Error> {
+        if !detail.has_markdown_source() {
+            return Err(Error::Parse(format!(
+                "Article {} has no Markdown source to resubmit",
+                detail.oId
+            )));
+        }
+        let content = &detail.source;
+
+        Ok(ArticlePost {
+            title: detail.title.clone(),
+            content: content.to_string(),
+            tags: detail.tags.clone(),
+            commentable: detail.commentable,
+            notifyFollowers: false,
+            type_: detail.type_.clone(),
+            showInList: detail.showInList as u32,
+            rewardContent: (!detail.rewardContent.is_empty()).then(|| detail.rewardContent.clone()),
+            rewardPoint: (detail.rewardPoint > 0).then(|| detail.rewardPoint.to_string()),
+            anonymous: Some(detail.anonymous),
+            offerPoint: (detail.offerPoint > 0).then_some(detail.offerPoint as u32),
+            editorType: EditorType::from_index(detail.editorType),
+        })
+    }
+}
+
+/// 文章局部更新，仅覆盖设置了值的字段
+#[derive(Clone, Debug, Default)]
+#[allow(non_snake_case)]
+pub struct ArticlePatch {
+    /// 帖子标题
+    pub title: Option<String>,
+    /// 帖子内容（Markdown 源文本）
+    pub content: Option<String>,
+    /// 帖子标签
+    pub tags: Option<String>,
+    /// 是否允许评论
+    pub commentable: Option<bool>,
+    /// 是否通知帖子关注者
+    pub notifyFollowers: Option<bool>,
+    /// 帖子类型
+    pub type_: Option<ArticleType>,
+    /// 是否在列表展示
+    pub showInList: Option<bool>,
+    /// 打赏内容
+    pub rewardContent: Option<String>,
+    /// 打赏积分
+    pub rewardPoint: Option<String>,
+    /// 是否匿名
+    pub anonymous: Option<bool>,
+    /// 提问悬赏积分
+    pub offerPoint: Option<u32>,
+    /// 编辑器类型
+    pub editorType: Option<EditorType>,
+}
+
+impl ArticlePatch {
+    /// 是否没有任何需要修改的字段
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none()
+            && self.content.is_none()
+            && self.tags.is_none()
+            && self.commentable.is_none()
+            && self.notifyFollowers.is_none()
+            && self.type_.is_none()
+            && self.showInList.is_none()
+            && self.rewardContent.is_none()
+            && self.rewardPoint.is_none()
+            && self.anonymous.is_none()
+            && self.offerPoint.is_none()
+            && self.editorType.is_none()
+    }
+
+    /// 将修改合并到发帖信息
+    ///
+    /// - `post` 原发帖信息
+    pub fn apply(&self, post: &mut ArticlePost) {
+        if let Some(title) = &self.title {
+            post.title = title.clone();
+        }
+        if let Some(content) = &self.content {
+            post.content = content.clone();
+        }
+        if let Some(tags) = &self.tags {
+            post.tags = tags.clone();
+        }
+        if let Some(commentable) = self.commentable {
+            post.commentable = commentable;
+        }
+        if let Some(notify) = self.notifyFollowers {
+            post.notifyFollowers = notify;
+        }
+        if let Some(type_) = &self.type_ {
+            post.type_ = type_.clone();
+        }
+        if let Some(show) = self.showInList {
+            post.showInList = show as u32;
+        }
+        if let Some(reward_content) = &self.rewardContent {
+            post.rewardContent = Some(reward_content.clone());
+        }
+        if let Some(reward_point) = &self.rewardPoint {
+            post.rewardPoint = Some(reward_point.clone());
+        }
+        if let Some(anonymous) = self.anonymous {
+            post.anonymous = Some(anonymous);
+        }
+        if let Some(offer_point) = self.offerPoint {
+            post.offerPoint = Some(offer_point);
+        }
+        if let Some(editor_type) = &self.editorType {
+            post.editorType = editor_type.clone();
+        }
+    }
 }
 
 /// 文章标签
@@ -175,7 +339,7 @@ impl ArticleTag {
 }
 
 /// 投票状态，点赞与否
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub enum VoteStatus {
     /// 未投票
     #[default]
@@ -194,6 +358,21 @@ impl VoteStatus {
             _ => VoteStatus::Normal,
         }
     }
+
+    /// 根据点赞接口的响应计算投票后的状态
+    ///
+    /// 服务端返回的 `type` 是本次投票前的状态：-1 未投票、0 点赞、1 点踩；
+    /// 重复同一种投票会取消投票。
+    ///
+    /// - `like` 本次为点赞还是点踩
+    /// - `previous` 响应中的 `type`
+    pub fn after_vote(like: bool, previous: i64) -> Self {
+        match (like, previous) {
+            (true, 0) | (false, 1) => VoteStatus::Normal,
+            (true, _) => VoteStatus::Up,
+            (false, _) => VoteStatus::Down,
+        }
+    }
 }
 
 /// 文章状态
@@ -219,6 +398,7 @@ impl ArticleStatus {
     }
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for ArticleStatus {
     fn default() -> Self {
         Self::Normal
@@ -507,6 +687,11 @@ impl ArticleComment {
     pub fn from_value(data: &Value) -> Result<Self, Error> {
         parse_with_float_fallback(data, "ArticleComment")
     }
+
+    /// 评论创建时间，无法解析时返回 `None`
+    pub fn create_datetime(&self) -> Option<DateTime<Utc>> {
+        parse_time(&self.createTime).or_else(|| parse_time(&self.createTimeStr))
+    }
 }
 
 /// 分页信息
@@ -527,8 +712,8 @@ impl Pagination {
     }
 }
 
-/// 帖子类型
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// 帖子类型，序列化为服务端使用的数字 `articleType`
+#[derive(Clone, Debug, PartialEq, Eq)]
 #[repr(u8)]
 #[derive(Default)]
 pub enum ArticleType {
@@ -554,6 +739,24 @@ impl ArticleType {
     }
 }
 
+impl Serialize for ArticleType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_u8(self.clone() as u8)
+    }
+}
+
+impl<'de> Deserialize<'de> for ArticleType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_type(deserializer)
+    }
+}
+
 fn default_article_type() -> ArticleType {
     ArticleType::Unknown
 }
@@ -865,6 +1068,16 @@ impl ArticleDetail {
         parse_with_float_fallback(data, "ArticleDetail")
     }
 
+    /// 帖子创建时间，无法解析时返回 `None`
+    pub fn create_datetime(&self) -> Option<DateTime<Utc>> {
+        parse_time(&self.createTime).or_else(|| parse_time(&self.createTimeStr))
+    }
+
+    /// 帖子最后更新时间，无法解析时返回 `None`
+    pub fn update_datetime(&self) -> Option<DateTime<Utc>> {
+        parse_time(&self.updateTime).or_else(|| parse_time(&self.updateTimeStr))
+    }
+
     /// 返回帖子 Markdown 源文本。
     ///
     /// 优先使用 `articleOriginalContent`，若为空则回退到 `articleContent`。
@@ -899,21 +1112,28 @@ pub fn deserialize_articles<'de, D>(deserializer: D) -> Result<Vec<ArticleDetail
 where
     D: Deserializer<'de>,
 {
-    let arr: Vec<Value> = Deserialize::deserialize(deserializer)?;
-    arr.into_iter()
-        .map(|v| ArticleDetail::from_value(&v))
+    let data: Value = Deserialize::deserialize(deserializer)?;
+    as_array_or_empty(&data)
+        .map_err(serde::de::Error::custom)?
+        .iter()
+        .map(ArticleDetail::from_value)
         .collect::<Result<Vec<_>, _>>()
         .map_err(serde::de::Error::custom)
 }
 
 /// 文章列表
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Default, Deserialize)]
 #[allow(non_snake_case)]
 pub struct ArticleList {
     /// 文章列表
-    #[serde(rename = "articles", deserialize_with = "deserialize_articles")]
+    #[serde(
+        rename = "articles",
+        default,
+        deserialize_with = "deserialize_articles"
+    )]
     pub list: Vec<ArticleDetail>,
     /// 分页信息
+    #[serde(default)]
     pub pagination: Pagination,
     /// 标签信息，仅查询标签下文章列表有效
     pub tag: Option<ArticleTag>,
@@ -921,6 +1141,9 @@ pub struct ArticleList {
 
 impl ArticleList {
     pub fn from_value(data: &Value) -> Result<Self, Error> {
+        if as_array_or_empty(data).is_ok_and(|arr| arr.is_empty()) {
+            return Ok(ArticleList::default());
+        }
         parse_with_float_fallback(data, "ArticleList")
     }
 }
@@ -1008,3 +1231,161 @@ impl CommentPost {
             .map_err(|e| Error::Parse(format!("Failed to serialize CommentPost: {}", e)))
     }
 }
+
+/// 文章评论的一页
+#[derive(Clone, Debug, Default)]
+pub struct CommentPage {
+    /// 本页评论
+    pub comments: Vec<ArticleComment>,
+    /// 页码
+    pub page: u32,
+    /// 分页信息，仅服务端返回时有值
+    pub pagination: Option<Pagination>,
+}
+
+impl CommentPage {
+    /// 从文章详情响应的 `data` 中读取一页评论
+    ///
+    /// - `data` 响应数据，评论列表位于 `article.articleComments`，分页信息位于 `pagination`
+    /// - `page` 页码
+    pub fn from_value(data: &Value, page: u32) -> Result<Self, Error> {
+        let comments = as_array_or_empty(&data["article"]["articleComments"])?
+            .iter()
+            .map(ArticleComment::from_value)
+            .collect::<Result<Vec<_>, _>>()?;
+        let pagination = match data.get("pagination") {
+            Some(v) if v.is_object() => Some(Pagination::from_value(v)?),
+            _ => None,
+        };
+        Ok(Self {
+            comments,
+            page,
+            pagination,
+        })
+    }
+
+    /// 是否还有下一页，没有分页信息时视为没有
+    pub fn has_more(&self) -> bool {
+        self.pagination
+            .as_ref()
+            .is_some_and(|pagination| self.page < pagination.count)
+    }
+}
+
+/// 感谢文章或评论的结果
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ThankResult {
+    /// 服务端返回的提示信息
+    pub msg: String,
+    /// 感谢后的感谢数，仅服务端返回时有值
+    pub thanked_cnt: Option<u64>,
+}
+
+impl ThankResult {
+    /// 从感谢接口的响应中读取结果
+    ///
+    /// - `data` 响应 JSON，感谢数可能位于顶层或 `data` 中
+    ///
+    /// 已感谢过时返回 [`Error::AlreadyThanked`]，其他失败返回 [`Error::Api`]
+    pub fn from_value(data: &Value) -> Result<Self, Error> {
+        let msg = data["msg"].as_str().unwrap_or_default().to_string();
+        if data.get("code").and_then(Value::as_i64).unwrap_or(0) != 0 {
+            if msg.contains("已经感谢") || msg.contains("已感谢") {
+                return Err(Error::AlreadyThanked(msg));
+            }
+            return Err(Error::from_response(data, "API error"));
+        }
+
+        let thanked_cnt = ["thankedCnt", "commentThankCnt", "articleThankCnt"]
+            .iter()
+            .flat_map(|key| [&data[*key], &data["data"][*key]])
+            .find_map(|v| v.as_u64().or_else(|| v.as_str()?.parse().ok()));
+        Ok(Self { msg, thanked_cnt })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ArticleDetail, ArticlePost, ArticleType, CommentPage, ThankResult, VoteStatus,
+        deserialize_type,
+    };
+    use crate::utils::error::Error;
+    use serde_json::json;
+
+    #[test]
+    fn thank_result_reads_count_and_already_thanked() {
+        let result = ThankResult::from_value(&json!({ "code": 0, "data": { "thankedCnt": 3 } }))
+            .expect("should parse");
+        assert_eq!(result.thanked_cnt, Some(3));
+
+        let result = ThankResult::from_value(&json!({ "code": 0, "msg": "" })).expect("ok");
+        assert_eq!(result.thanked_cnt, None);
+
+        let err = ThankResult::from_value(&json!({ "code": -1, "msg": "你已经感谢过了" }));
+        assert!(matches!(err, Err(Error::AlreadyThanked(_))));
+        let err = ThankResult::from_value(&json!({ "code": -1, "msg": "积分不足" }));
+        assert!(matches!(err, Err(Error::Api { .. })));
+    }
+
+    #[test]
+    fn comment_page_uses_pagination_when_present() {
+        let data = json!({
+            "article": { "articleComments": [{ "oId": "1", "commentContent": "hi" }] },
+            "pagination": { "paginationPageCount": 2, "paginationPageNums": [1, 2] }
+        });
+        let page = CommentPage::from_value(&data, 1).expect("should parse");
+        assert_eq!(page.comments.len(), 1);
+        assert!(page.has_more());
+
+        let page = CommentPage::from_value(&json!({ "article": {} }), 1).expect("ok");
+        assert!(page.pagination.is_none());
+        assert!(!page.has_more());
+    }
+
+    #[test]
+    fn article_type_round_trips_as_integer() {
+        let value = serde_json::to_value(ArticleType::Question).expect("serialize");
+        assert_eq!(value, json!(5));
+        assert_eq!(
+            deserialize_type(value).expect("deserialize"),
+            ArticleType::Question
+        );
+        let value = serde_json::to_value(ArticleType::Normal).expect("serialize");
+        assert_eq!(
+            serde_json::from_value::<ArticleType>(value).expect("deserialize"),
+            ArticleType::Normal
+        );
+    }
+
+    #[test]
+    fn vote_toggles_off_when_repeated() {
+        assert_eq!(VoteStatus::after_vote(true, -1), VoteStatus::Up);
+        assert_eq!(VoteStatus::after_vote(true, 0), VoteStatus::Normal);
+        assert_eq!(VoteStatus::after_vote(true, 1), VoteStatus::Up);
+        assert_eq!(VoteStatus::after_vote(false, 1), VoteStatus::Normal);
+        assert_eq!(VoteStatus::after_vote(false, 0), VoteStatus::Down);
+    }
+
+    #[test]
+    fn from_detail_only_submits_markdown_source() {
+        let detail = ArticleDetail {
+            oId: "1".to_string(),
+            content: "<p>hello</p>".to_string(),
+            source: "hello".to_string(),
+            ..Default::default()
+        };
+        let post = ArticlePost::from_detail(&detail).expect("has source");
+        assert_eq!(post.content, "hello");
+
+        let detail = ArticleDetail {
+            oId: "1".to_string(),
+            content: "<p>hello</p>".to_string(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            ArticlePost::from_detail(&detail),
+            Err(Error::Parse(_))
+        ));
+    }
+}
@@ -1,7 +1,8 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Deserializer};
 use serde_json::Value;
 
-use crate::utils::error::Error;
+use crate::utils::{error::Error, parse_time};
 
 /// 清风明月内容
 #[derive(Clone, Debug, Deserialize)]
@@ -48,6 +49,16 @@ impl BreezemoonContent {
         serde_json::from_value(data.clone())
             .map_err(|e| Error::Parse(format!("Failed to parse BreezemoonContent: {}", e)))
     }
+
+    /// 创建时间，无法解析时返回 `None`
+    pub fn created_datetime(&self) -> Option<DateTime<Utc>> {
+        parse_time(&self.created).or_else(|| parse_time(&self.createTime))
+    }
+
+    /// 最后更新时间，无法解析时返回 `None`
+    pub fn updated_datetime(&self) -> Option<DateTime<Utc>> {
+        parse_time(&self.updated)
+    }
 }
 
 fn de_string_lossy<'de, D>(deserializer: D) -> Result<String, D::Error>
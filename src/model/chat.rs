@@ -1,4 +1,8 @@
-use crate::{impl_str_enum, utils::error::Error};
+use crate::model::user::UserId;
+use crate::{
+    impl_str_enum,
+    utils::{error::Error, strip_html},
+};
 use serde::Deserialize;
 use serde_json::Value;
 
@@ -6,7 +10,7 @@ use serde_json::Value;
 #[allow(non_snake_case)]
 pub struct ChatData {
     #[serde(default)]
-    pub toId: String,
+    pub toId: UserId,
     #[serde(default)]
     pub preview: String,
     #[serde(default)]
@@ -22,26 +26,64 @@ pub struct ChatData {
     #[serde(default)]
     pub time: String,
     #[serde(default)]
-    pub fromId: String,
+    pub fromId: UserId,
     #[serde(default)]
     pub senderUserName: String,
     #[serde(default)]
     pub content: String,
     #[serde(default)]
     pub receiverUserName: String,
+    /// 会话未读消息数，仅 [`Chat::list`](crate::api::chat::Chat::list) 返回的会话有效
+    #[serde(default)]
+    pub unreadCount: u64,
 }
 impl ChatData {
     pub fn from_value(data: &Value) -> Result<Self, Error> {
         serde_json::from_value(data.clone())
             .map_err(|e| Error::Parse(format!("Failed to parse ChatData: {}", e)))
     }
+
+    /// 消息纯文本，去除 `content` 中的 HTML 标签
+    pub fn plain_text(&self) -> String {
+        strip_html(&self.content)
+    }
+}
+
+/// 私聊历史消息的一页
+#[derive(Clone, Debug, Default)]
+pub struct ChatHistoryPage {
+    /// 本页消息
+    pub messages: Vec<ChatData>,
+    /// 页码
+    pub page: u32,
+    /// 每页消息数量
+    pub size: u32,
+    /// 是否可能还有下一页，本页消息数量不足 `size` 时为 `false`
+    pub has_more: bool,
+}
+
+impl ChatHistoryPage {
+    /// 由一页消息构造分页结果，根据消息数量与每页数量推断是否还有下一页
+    ///
+    /// - `messages` 本页消息
+    /// - `page` 页码
+    /// - `size` 每页消息数量
+    pub fn new(messages: Vec<ChatData>, page: u32, size: u32) -> Self {
+        let has_more = size > 0 && messages.len() >= size as usize;
+        Self {
+            messages,
+            page,
+            size,
+            has_more,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize)]
 #[allow(non_snake_case)]
 pub struct ChatNotice {
     pub command: String,
-    pub userId: String,
+    pub userId: UserId,
     pub preview: String,
     pub senderAvatar: String,
     pub senderUserName: String,
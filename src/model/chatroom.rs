@@ -1,7 +1,9 @@
 use crate::impl_str_enum;
 use crate::model::reaction::ReactionSummaryItem;
-use crate::model::user::{Metal, to_metal};
+use crate::model::redpacket::RedPacketMessage;
+use crate::model::user::{Metal, UserId, to_metal};
 use crate::utils::error::Error;
+use crate::utils::strip_html;
 use serde::{Deserialize, Deserializer};
 use serde_json::Value;
 use std::str::FromStr;
@@ -52,11 +54,106 @@ pub enum ClientType {
     Other,
 }
 
+/// 聊天室历史消息内容类型
+///
+/// 决定返回的 [`ChatRoomMsg::content`] 的格式：`Html` 为渲染后的 HTML，`Markdown` 为原始 Markdown。
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ChatContentType {
     Markdown,
     Html,
 }
 
+/// 聊天室发送选项，用于 [`crate::api::chatroom::ChatRoom::send_with`]
+///
+/// `client` 与 `version` 组成消息的来源（`via`）标识，如 `Idea/1.0.0`；
+/// `markdown` 为 `false` 时会转义 Markdown 标记，消息按纯文本原样显示。
+#[derive(Clone, Debug)]
+pub struct SendOptions {
+    /// 是否按 Markdown 渲染，默认为 `true`
+    pub markdown: bool,
+    /// 客户端类型
+    pub client: ClientType,
+    /// 客户端版本号，为空时使用 `last`
+    pub version: Option<String>,
+}
+
+impl SendOptions {
+    /// 创建发送选项，默认按 Markdown 发送
+    ///
+    /// - `client` 客户端类型 [ClientType]
+    pub fn new(client: ClientType) -> Self {
+        Self {
+            markdown: true,
+            client,
+            version: None,
+        }
+    }
+
+    /// 设置是否按 Markdown 渲染
+    ///
+    /// - `markdown` 为 `false` 时按纯文本发送
+    pub fn markdown(mut self, markdown: bool) -> Self {
+        self.markdown = markdown;
+        self
+    }
+
+    /// 设置客户端版本号
+    ///
+    /// - `version` 版本号
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    /// 客户端标识，格式为 `客户端/版本`
+    pub fn client_tag(&self) -> String {
+        format!(
+            "{}/{}",
+            self.client.as_str(),
+            self.version.as_deref().unwrap_or("last")
+        )
+    }
+
+    /// 按选项处理消息内容，纯文本模式下转义 Markdown 标记
+    ///
+    /// 只转义会被解析为 Markdown 语法的字符：强调、代码、链接、引用/HTML 与表格标记在任意位置转义，
+    /// 标题与列表标记只在行首转义，其余标点保持原样。
+    ///
+    /// - `content` 消息内容
+    pub fn render(&self, content: &str) -> String {
+        if self.markdown {
+            return content.to_string();
+        }
+        let mut escaped = String::with_capacity(content.len());
+        let mut line_start = true;
+        for c in content.chars() {
+            let special =
+                MARKDOWN_INLINE.contains(&c) || (line_start && MARKDOWN_LINE_START.contains(&c));
+            if special {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+            if c == '\n' {
+                line_start = true;
+            } else if !c.is_whitespace() {
+                line_start = false;
+            }
+        }
+        escaped
+    }
+}
+
+impl Default for SendOptions {
+    fn default() -> Self {
+        Self::new(ClientType::Rust).version(env!("CARGO_PKG_VERSION"))
+    }
+}
+
+/// 任意位置都需要转义的 Markdown 字符
+const MARKDOWN_INLINE: &[char] = &['\\', '`', '*', '_', '[', ']', '<', '>', '|', '~'];
+/// 只在行首有特殊含义的 Markdown 字符（标题、列表）
+const MARKDOWN_LINE_START: &[char] = &['#', '-', '+'];
+
 /// chatroom get 接口获取 oId 的相关消息类型
 #[repr(u8)]
 pub enum ChatRoomMessageMode {
@@ -90,6 +187,23 @@ pub enum ChatRoomMessageType {
     ChatReaction,
 }
 
+impl ChatRoomMessageType {
+    /// SDK 已支持的消息类型
+    pub fn values() -> Vec<&'static str> {
+        vec![
+            "online",
+            "discussChanged",
+            "revoke",
+            "msg",
+            "redPacket",
+            "redPacketStatus",
+            "barrager",
+            "customMessage",
+            "chatreaction",
+        ]
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct CustomMsg {
     pub message: String,
@@ -118,7 +232,7 @@ pub struct ChatReactionMsg {
     pub summary: Vec<ReactionSummaryItem>,
     /// 本次触发操作的用户 id。
     #[serde(default)]
-    pub actorUserId: String,
+    pub actorUserId: UserId,
     /// 该用户本次操作后最终选中的表情值。
     #[serde(default)]
     pub actorReaction: String,
@@ -142,14 +256,111 @@ pub struct BarragerCost {
     pub unit: String,
 }
 
-// /// 聊天天气消息
-// pub struct WeatherMessage {
-//     city: String,
-//     description: String,
-//     data: WeatherData,
-// }
+/// 聊天音乐消息
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct MusicMsg {
+    /// 歌曲名
+    pub title: String,
+    /// 音频地址
+    pub source: String,
+    /// 封面地址
+    #[serde(rename = "coverURL")]
+    pub cover: String,
+    /// 来源，如点歌平台
+    pub from: String,
+    /// 原始消息内容，保留 SDK 尚未解析的字段
+    #[serde(skip)]
+    pub raw: Value,
+}
+
+impl MusicMsg {
+    pub fn from_value(value: &Value) -> Result<Self, Error> {
+        let mut msg: Self = serde_json::from_value(value.clone())
+            .map_err(|e| Error::Parse(format!("Failed to parse MusicMsg: {}", e)))?;
+        msg.raw = value.clone();
+        Ok(msg)
+    }
+}
+
+/// 聊天天气消息
+#[derive(Clone, Debug, Default)]
+pub struct WeatherMsg {
+    /// 城市
+    pub city: String,
+    /// 天气描述
+    pub description: String,
+    /// 每日天气
+    pub data: Vec<WeatherData>,
+    /// 原始消息内容，保留 SDK 尚未解析的字段
+    pub raw: Value,
+}
+
+impl WeatherMsg {
+    /// 解析天气消息内容
+    ///
+    /// 服务端以逗号分隔的字符串给出每日的 `date`、`weatherCode`、`min`、`max`，
+    /// 也兼容数组形式；无法识别的天气代码记为 [`WeatherCode::Unknown`]。
+    pub fn from_value(value: &Value) -> Result<Self, Error> {
+        if !value.is_object() {
+            return Err(Error::Parse(
+                "Failed to parse WeatherMsg: content is not an object".to_string(),
+            ));
+        }
+
+        let text = |keys: &[&str]| {
+            keys.iter()
+                .find_map(|k| value.get(*k).and_then(Value::as_str))
+                .unwrap_or_default()
+                .to_string()
+        };
+        let list = |key: &str| -> Vec<String> {
+            match value.get(key) {
+                Some(Value::String(s)) => s
+                    .split(',')
+                    .map(|v| v.trim().to_string())
+                    .filter(|v| !v.is_empty())
+                    .collect(),
+                Some(Value::Array(arr)) => arr
+                    .iter()
+                    .map(|v| match v {
+                        Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            }
+        };
+
+        let dates = list("date");
+        let codes = list("weatherCode");
+        let mins = list("min");
+        let maxs = list("max");
+        let data = dates
+            .iter()
+            .enumerate()
+            .map(|(i, date)| WeatherData {
+                date: date.clone(),
+                code: codes
+                    .get(i)
+                    .and_then(|c| WeatherCode::from_str(c).ok())
+                    .unwrap_or_default(),
+                min: mins.get(i).cloned().unwrap_or_default(),
+                max: maxs.get(i).cloned().unwrap_or_default(),
+            })
+            .collect();
+
+        Ok(Self {
+            city: text(&["t", "city"]),
+            description: text(&["st", "description"]),
+            data,
+            raw: value.clone(),
+        })
+    }
+}
 
 /// 聊天天气消息详情
+#[derive(Clone, Debug)]
 pub struct WeatherData {
     pub date: String,
     pub code: WeatherCode,
@@ -163,7 +374,7 @@ pub struct ChatRoomSource {
     pub version: String,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub enum WeatherCode {
     ClearDay,
     ClearNight,
@@ -185,6 +396,9 @@ pub enum WeatherCode {
     StormRain,
     StormSnow,
     Wind,
+    /// SDK 尚未支持的天气代码
+    #[default]
+    Unknown,
 }
 
 /// 聊天消息
@@ -194,7 +408,7 @@ pub struct ChatRoomMsg<T = Value> {
     pub r#type: ChatRoomMessageType,
     pub oId: String,
     pub time: String,
-    pub userOId: String,
+    pub userOId: UserId,
     pub userName: String,
     pub userNickname: String,
     pub userAvatarURL: String,
@@ -302,6 +516,7 @@ impl_str_enum!(WeatherCode {
     StormRain => "STORM_RAIN",
     StormSnow => "STORM_SNOW",
     Wind => "WIND",
+    Unknown => "UNKNOWN",
 });
 
 impl_str_enum!(ChatRoomMessageMode{
@@ -332,6 +547,150 @@ impl ChatRoomMsg {
             &self.userNickname
         }
     }
+
+    /// 消息纯文本，去除 `content` 中的 HTML 标签；非文本消息（如红包、音乐）返回空字符串
+    pub fn plain_text(&self) -> String {
+        self.content.as_str().map(strip_html).unwrap_or_default()
+    }
+
+    /// 按请求的内容类型整理消息
+    ///
+    /// `content` 保持为请求的格式；请求 `Markdown` 时若服务端未返回 `md`，
+    /// 则用 `content` 补全，保证 `md` 始终为 Markdown 源文本。
+    ///
+    /// - `type_` 请求时使用的内容类型 [ChatContentType]
+    pub fn apply_content_type(&mut self, type_: &ChatContentType) {
+        if *type_ == ChatContentType::Markdown
+            && self.md.is_empty()
+            && let Value::String(content) = &self.content
+        {
+            self.md = content.clone();
+        }
+    }
+}
+
+/// 聊天室消息内容分类，由 [`ChatRoomMsg::classify`] 得到
+#[derive(Clone, Debug)]
+pub enum ChatMsgKind {
+    /// 普通文本消息
+    Text(String),
+    /// 红包消息
+    RedPacket(RedPacketMessage),
+    /// 弹幕
+    Barrager { color: String, content: String },
+    /// 音乐消息
+    Music(MusicMsg),
+    /// 天气消息
+    Weather(WeatherMsg),
+}
+
+/// 读取 `[tag]...[/tag]` 包裹的内容
+fn unwrap_markup<'a>(text: &'a str, tag: &str) -> Option<&'a str> {
+    text.trim()
+        .strip_prefix(&format!("[{}]", tag))?
+        .strip_suffix(&format!("[/{}]", tag))
+}
+
+impl ChatRoomMsg {
+    /// 识别消息内容的类型并解析其中的数据
+    ///
+    /// 只识别已解析为 JSON 的 `msgType` 内容（红包、音乐、天气），用于实时推送的消息；
+    /// 无法识别或解析失败时作为普通文本。
+    pub fn classify(&self) -> ChatMsgKind {
+        let text = match &self.content {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        let data = match &self.content {
+            Value::Object(_) => Some(self.content.clone()),
+            _ => serde_json::from_str::<Value>(&text)
+                .ok()
+                .filter(|v| v["msgType"].is_string()),
+        };
+
+        if let Some(data) = data {
+            let kind = match data["msgType"].as_str() {
+                Some("redPacket") => RedPacketMessage::from_value(&data)
+                    .ok()
+                    .map(ChatMsgKind::RedPacket),
+                Some("music") => MusicMsg::from_value(&data).ok().map(ChatMsgKind::Music),
+                Some("weather") => WeatherMsg::from_value(&data).ok().map(ChatMsgKind::Weather),
+                _ => None,
+            };
+            return kind.unwrap_or(ChatMsgKind::Text(text));
+        }
+
+        ChatMsgKind::Text(text)
+    }
+
+    /// 识别历史消息内容的类型并解析其中的数据
+    ///
+    /// 在 [`ChatRoomMsg::classify`] 的基础上，额外识别 `content` 中保留的
+    /// `[redpacket]{...}[/redpacket]`、`[barrager]{...}[/barrager]` 原始发送内容。
+    /// 不读取 `md` 字段；原始红包数据缺少领取信息等字段时作为普通文本。
+    pub fn classify_history(&self) -> ChatMsgKind {
+        let kind = self.classify();
+        let ChatMsgKind::Text(text) = &kind else {
+            return kind;
+        };
+
+        if let Some(inner) = unwrap_markup(text, "redpacket")
+            && let Ok(mut data) = serde_json::from_str::<Value>(inner)
+            && data.is_object()
+        {
+            if data["msgType"].is_null() {
+                data["msgType"] = Value::from("redPacket");
+            }
+            if let Ok(msg) = RedPacketMessage::from_value(&data) {
+                return ChatMsgKind::RedPacket(msg);
+            }
+        }
+        if let Some(inner) = unwrap_markup(text, "barrager")
+            && let Ok(data) = serde_json::from_str::<Value>(inner)
+        {
+            return ChatMsgKind::Barrager {
+                color: data["color"].as_str().unwrap_or_default().to_string(),
+                content: data["content"].as_str().unwrap_or_default().to_string(),
+            };
+        }
+
+        kind
+    }
+}
+
+impl<T> ChatRoomMsg<T> {
+    /// 转换消息内容，其余字段保持不变
+    ///
+    /// - `f` 内容转换函数
+    pub fn try_map_content<U>(
+        self,
+        f: impl FnOnce(&T) -> Result<U, Error>,
+    ) -> Result<ChatRoomMsg<U>, Error> {
+        let content = f(&self.content)?;
+        Ok(self.map_content(|_| content))
+    }
+
+    /// 转换消息内容，其余字段保持不变
+    ///
+    /// - `f` 内容转换函数
+    pub fn map_content<U>(self, f: impl FnOnce(T) -> U) -> ChatRoomMsg<U> {
+        ChatRoomMsg {
+            r#type: self.r#type,
+            oId: self.oId,
+            time: self.time,
+            userOId: self.userOId,
+            userName: self.userName,
+            userNickname: self.userNickname,
+            userAvatarURL: self.userAvatarURL,
+            sysMetal: self.sysMetal,
+            content: f(self.content),
+            md: self.md,
+            client: self.client,
+            via: self.via,
+            reactionSummary: self.reactionSummary,
+            currentUserReaction: self.currentUserReaction,
+        }
+    }
 }
 
 impl BarragerMsg {
@@ -405,7 +764,7 @@ impl<'de> Deserialize<'de> for ChatRoomMsg<Value> {
             r#type,
             oId: raw.oId,
             time: raw.time,
-            userOId: raw.userOId.to_string(),
+            userOId: UserId::from_value(&raw.userOId),
             userName: raw.userName,
             userNickname: raw.userNickname,
             userAvatarURL: raw.userAvatarURL,
@@ -421,20 +780,103 @@ impl<'de> Deserialize<'de> for ChatRoomMsg<Value> {
 }
 
 impl BarragerCost {
+    /// 解析弹幕花费，如 `5积分`
+    ///
+    /// 开头的数字为花费，其余部分为单位；没有数字时（如 `免费`）花费记为 0，
+    /// 单位为空时默认为 `积分`。
     pub fn from_value(value: &Value) -> Self {
         let content = value
             .get("data")
             .and_then(|v| v.as_str())
-            .unwrap_or("5积分");
-        let mut parts = content
-            .split(|c: char| !c.is_alphanumeric())
-            .filter(|s| !s.is_empty());
-        let cost = parts
-            .next()
-            .and_then(|s| s.parse::<u32>().ok())
-            .unwrap_or(0);
-        let unit = parts.next().unwrap_or("积分").to_string();
+            .unwrap_or("5积分")
+            .trim();
+        let digits_end = content
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(content.len());
+        let cost = content[..digits_end].parse::<u32>().unwrap_or(0);
+        let unit = content[digits_end..].trim();
+        let unit = if unit.is_empty() { "积分" } else { unit }.to_string();
 
         Self { cost, unit }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{BarragerCost, ChatMsgKind, ChatRoomMsg, ClientType, SendOptions};
+    use serde_json::json;
+
+    fn msg_with_content(content: &str) -> ChatRoomMsg {
+        ChatRoomMsg::from_value(&json!({
+            "oId": "1",
+            "time": "2024-01-01 00:00:00",
+            "userOId": 7,
+            "userName": "alice",
+            "userNickname": "",
+            "userAvatarURL": "",
+            "content": content,
+        }))
+        .expect("should parse")
+    }
+
+    #[test]
+    fn classify_recognizes_markup_in_history() {
+        let msg = msg_with_content(
+            r#"[redpacket]{"type":"random","money":32,"count":2,"got":1,"msg":"摸鱼","senderId":"7","recivers":[]}[/redpacket]"#,
+        );
+        match msg.classify_history() {
+            ChatMsgKind::RedPacket(rp) => {
+                assert_eq!((rp.money, rp.count, rp.got), (32, 2, 1));
+                assert_eq!(rp.senderId, "7");
+            }
+            other => panic!("unexpected kind: {:?}", other),
+        }
+        // 实时消息不解析原始标记
+        assert!(matches!(msg.classify(), ChatMsgKind::Text(_)));
+
+        // 缺少领取信息的原始红包数据不补全，作为普通文本
+        let msg = msg_with_content(
+            r#"[redpacket]{"type":"random","money":32,"count":2,"msg":"摸鱼","recivers":[]}[/redpacket]"#,
+        );
+        assert!(matches!(msg.classify_history(), ChatMsgKind::Text(_)));
+
+        let msg = msg_with_content(r##"[barrager]{"color":"#ff0000","content":"hi"}[/barrager]"##);
+        assert!(matches!(
+            msg.classify_history(),
+            ChatMsgKind::Barrager { color, content } if color == "#ff0000" && content == "hi"
+        ));
+
+        let mut msg = msg_with_content("<p>hi</p>");
+        msg.md = r##"[barrager]{"color":"#ff0000","content":"hi"}[/barrager]"##.to_string();
+        assert!(matches!(msg.classify_history(), ChatMsgKind::Text(_)));
+
+        let msg = msg_with_content("<p>[redpacket]oops</p>");
+        assert!(matches!(msg.classify_history(), ChatMsgKind::Text(_)));
+    }
+
+    #[test]
+    fn barrager_cost_handles_malformed_data() {
+        let cost = BarragerCost::from_value(&json!({ "data": "5积分" }));
+        assert_eq!((cost.cost, cost.unit.as_str()), (5, "积分"));
+
+        let cost = BarragerCost::from_value(&json!({ "data": "" }));
+        assert_eq!((cost.cost, cost.unit.as_str()), (0, "积分"));
+
+        let cost = BarragerCost::from_value(&json!({ "data": "免费" }));
+        assert_eq!((cost.cost, cost.unit.as_str()), (0, "免费"));
+    }
+
+    #[test]
+    fn send_options_escape_plain_text() {
+        let options = SendOptions::new(ClientType::Idea).version("1.0.0");
+        assert_eq!(options.client_tag(), "IDEA/1.0.0");
+        assert_eq!(options.render("**hi**"), "**hi**");
+
+        let plain = options.markdown(false);
+        assert_eq!(plain.render("**hi** 你好"), "\\*\\*hi\\*\\* 你好");
+        // 普通标点不转义，行首的标题与列表标记才转义
+        assert_eq!(plain.render("你好, a.b (c)! 1-2"), "你好, a.b (c)! 1-2");
+        assert_eq!(plain.render("# t\n - x"), "\\# t\n \\- x");
+        assert_eq!(SendOptions::new(ClientType::Cli).client_tag(), "CLI/last");
+    }
+}
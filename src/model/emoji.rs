@@ -101,6 +101,40 @@ impl<'de> Deserialize<'de> for EmojiItem {
     }
 }
 
+/// 表情包，即一个分组及其中的全部表情，可直接用于渲染表情选择器。
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct EmojiPack {
+    /// 分组信息。
+    pub group: EmojiGroup,
+    /// 分组内的表情，按排序值升序排列。
+    pub emojis: Vec<EmojiItem>,
+}
+
+impl EmojiPack {
+    /// 组合分组与表情，表情按 `sort` 升序排列
+    ///
+    /// - `group` 分组
+    /// - `emojis` 分组内的表情
+    pub fn new(group: EmojiGroup, mut emojis: Vec<EmojiItem>) -> Self {
+        emojis.sort_by_key(|item| item.sort);
+        Self { group, emojis }
+    }
+
+    /// 表情包名称，即分组名称。
+    pub fn name(&self) -> &str {
+        &self.group.name
+    }
+
+    /// 按名称查找表情
+    ///
+    /// - `name` 表情名称
+    ///
+    /// 返回第一个同名表情
+    pub fn find(&self, name: &str) -> Option<&EmojiItem> {
+        self.emojis.iter().find(|item| item.name == name)
+    }
+}
+
 fn pick_string(value: &Value, keys: &[&str]) -> String {
     keys.iter()
         .find_map(|key| value.get(key).and_then(Value::as_str))
@@ -149,7 +183,7 @@ fn pick_bool(value: &Value, keys: &[&str]) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use super::EmojiItem;
+    use super::{EmojiGroup, EmojiItem, EmojiPack};
     use serde_json::json;
 
     #[test]
@@ -166,4 +200,29 @@ mod tests {
         assert_eq!(item.oId, "item-o-id");
         assert_eq!(item.url, "https://file.fishpi.cn/a.gif");
     }
+
+    #[test]
+    fn emoji_pack_sorts_items() {
+        let group = EmojiGroup::from_value(&json!({ "oId": "g1", "name": "常用" }))
+            .expect("emoji group should parse");
+        let items = [("b", 2), ("a", 1)]
+            .into_iter()
+            .map(|(name, sort)| {
+                EmojiItem::from_value(&json!({
+                    "name": name,
+                    "url": format!("https://file.fishpi.cn/{name}.png"),
+                    "sort": sort,
+                }))
+                .expect("emoji item should parse")
+            })
+            .collect();
+
+        let pack = EmojiPack::new(group, items);
+        assert_eq!(pack.name(), "常用");
+        assert_eq!(pack.emojis[0].name, "a");
+        assert_eq!(
+            pack.find("b").map(|item| item.url.as_str()),
+            Some("https://file.fishpi.cn/b.png")
+        );
+    }
 }
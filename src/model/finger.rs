@@ -1,7 +1,9 @@
+use std::collections::HashMap;
+
 use serde::Deserialize;
-use serde_json::Value;
+use serde_json::{Map, Value};
 
-use crate::{impl_str_enum, utils::error::Error};
+use crate::{impl_str_enum, model::user::UserId, utils::error::Error};
 
 /// 摸鱼大闯关信息
 #[derive(Debug, Clone, Deserialize)]
@@ -24,7 +26,7 @@ impl MoFishGame {
 #[allow(non_snake_case)]
 pub struct UserIP {
     pub latestLoginIP: String,
-    pub userId: String,
+    pub userId: UserId,
 }
 
 impl UserIP {
@@ -55,7 +57,8 @@ impl_str_enum!(UserBagType{
 });
 
 /// 用户背包信息
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
 #[allow(non_snake_case)]
 pub struct UserBag {
     /// 免签卡
@@ -66,11 +69,103 @@ pub struct UserBag {
     pub patchCheckinCard: u32,
     /// 摸鱼派一周年纪念勋章领取券
     pub metalTicket: u32,
+    /// SDK 尚未支持的物品及数量
+    #[serde(flatten)]
+    pub extra: HashMap<String, u32>,
 }
 
 impl UserBag {
     pub fn from_value(data: &Value) -> Result<Self, Error> {
-        serde_json::from_value(data.clone())
+        let data = data.get("data").filter(|v| v.is_object()).unwrap_or(data);
+        // 只保留数量字段，忽略 code、msg 等响应字段
+        let items: Map<String, Value> = data
+            .as_object()
+            .map(|obj| {
+                obj.iter()
+                    .filter(|(k, v)| {
+                        k.as_str() != "code" && v.as_u64().is_some_and(|n| n <= u32::MAX as u64)
+                    })
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        serde_json::from_value(Value::Object(items))
             .map_err(|e| Error::Parse(format!("Failed to parse UserBag: {}", e)))
     }
+
+    /// 物品总数，包括 SDK 尚未支持的物品
+    pub fn total(&self) -> u32 {
+        self.iter().map(|(_, count)| count).sum::<u32>() + self.extra.values().sum::<u32>()
+    }
+
+    /// 遍历已支持的物品及数量，未支持的物品见 [`UserBag::extra`]
+    pub fn iter(&self) -> impl Iterator<Item = (UserBagType, u32)> {
+        [
+            (UserBagType::Checkin1day, self.checkin1day),
+            (UserBagType::Checkin2days, self.checkin2days),
+            (UserBagType::PatchCheckinCard, self.patchCheckinCard),
+            (UserBagType::MetalTicket, self.metalTicket),
+        ]
+        .into_iter()
+    }
+}
+
+/// 用户管理概览，由多个金手指接口的结果组合而成
+///
+/// 各项查询相互独立，某项失败时对应字段为 `None`，错误信息记录在 `errors` 中。
+#[derive(Clone, Debug, Default)]
+pub struct ModerationProfile {
+    /// 用户名
+    pub user_name: String,
+    /// 最近登录 IP
+    pub ip: Option<UserIP>,
+    /// 积分
+    pub points: Option<u32>,
+    /// 当前活跃度
+    pub liveness: Option<f64>,
+    /// 背包
+    pub bag: Option<UserBag>,
+    /// 查询失败的项目及错误信息
+    pub errors: Vec<(String, String)>,
+}
+
+impl ModerationProfile {
+    /// 是否所有项目都查询成功
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{UserBag, UserIP};
+    use serde_json::json;
+
+    #[test]
+    fn user_ip_normalizes_numeric_user_id() {
+        let ip = UserIP::from_value(&json!({
+            "latestLoginIP": "127.0.0.1",
+            "userId": 1700000000000_i64
+        }))
+        .expect("should parse");
+        assert_eq!(ip.userId, "1700000000000");
+    }
+
+    #[test]
+    fn user_bag_keeps_unknown_items() {
+        let bag = UserBag::from_value(&json!({
+            "code": 0,
+            "msg": "",
+            "checkin1day": 2,
+            "metalTicket": 1,
+            "newItem": 3
+        }))
+        .expect("should parse");
+
+        assert_eq!(bag.checkin1day, 2);
+        assert_eq!(bag.extra.get("newItem"), Some(&3));
+        assert!(!bag.extra.contains_key("code"));
+        assert_eq!(bag.total(), 6);
+    }
 }
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
 
+use crate::model::user::UserId;
 use crate::utils::error::Error;
 
 fn to_md5(input: &str) -> String {
@@ -13,6 +14,7 @@ fn to_md5(input: &str) -> String {
 pub struct LoginData {
     #[serde(rename = "nameOrEmail")]
     pub username: String,
+    /// 密码的 MD5，[`LoginData::new`] 会自动计算
     #[serde(rename = "userPassword")]
     pub password: String,
     #[serde(rename = "mfaCode")]
@@ -20,6 +22,11 @@ pub struct LoginData {
 }
 
 impl LoginData {
+    /// 使用明文密码创建登录信息，密码会在这里计算 MD5
+    ///
+    /// - `username` 用户名或邮箱
+    /// - `password` 明文密码
+    /// - `mfa_code` 两步验证一次性密码
     pub fn new(username: &str, password: &str, mfa_code: Option<String>) -> Self {
         LoginData {
             username: username.to_string(),
@@ -28,6 +35,21 @@ impl LoginData {
         }
     }
 
+    /// 使用已计算好的密码 MD5 创建登录信息，不再重复计算
+    ///
+    /// 适用于“记住密码”时只保存了 MD5 的场景，保存的值即 [`LoginData::password`]。
+    ///
+    /// - `username` 用户名或邮箱
+    /// - `md5_hash` 密码的 MD5（32 位小写十六进制）
+    /// - `mfa_code` 两步验证一次性密码
+    pub fn from_hashed(username: &str, md5_hash: &str, mfa_code: Option<String>) -> Self {
+        LoginData {
+            username: username.to_string(),
+            password: md5_hash.to_ascii_lowercase(),
+            mfa_code,
+        }
+    }
+
     pub fn from_value(value: &Value) -> Result<Self, Error> {
         serde_json::from_value(value.clone())
             .map_err(|e| Error::Parse(format!("Failed to parse LoginData: {}", e)))
@@ -80,7 +102,7 @@ pub struct RegisterInfo {
     pub passwd: String,
     /// 用户 Id
     #[serde(rename = "userId")]
-    pub user_id: String,
+    pub user_id: UserId,
     /// 邀请人用户名
     pub r: Option<String>,
 }
@@ -97,7 +119,7 @@ impl Default for RegisterInfo {
         Self {
             role: "0".to_string(),
             passwd: String::new(),
-            user_id: String::new(),
+            user_id: UserId::default(),
             r: None,
         }
     }
@@ -235,7 +257,7 @@ impl std::fmt::Display for UploadResult {
 #[derive(Default)]
 pub struct UserLite {
     /// 用户昵称
-    #[serde(rename = "userNickname")]
+    #[serde(rename = "userNickname", default)]
     pub user_nickname: String,
     /// 用户名
     #[serde(rename = "userName")]
@@ -274,7 +296,7 @@ pub struct UserVipInfo {
     pub bold: bool,
     pub o_id: String,
     pub state: bool,
-    pub user_id: String,
+    pub user_id: UserId,
     pub lv_code: String,
     pub expires_at: u64,
     pub created_at: u64,
@@ -318,7 +340,7 @@ impl UserVipInfo {
         let bold = data["bold"].as_bool().unwrap_or(false);
         let o_id = data["oId"].as_str().unwrap_or("").to_string();
         let state = data["state"].as_i64().unwrap_or(0) == 1;
-        let user_id = data["userId"].as_str().unwrap_or("").to_string();
+        let user_id = UserId::from_value(&data["userId"]);
         let lv_code = data["lvCode"].as_str().unwrap_or("").to_string();
         let expires_at = data["expiresAt"].as_u64().unwrap_or(0);
         let created_at = data["createdAt"].as_u64().unwrap_or(0);
@@ -479,4 +501,63 @@ impl Log {
         serde_json::from_value(data.clone())
             .map_err(|e| Error::Parse(format!("Failed to parse Log: {}", e)))
     }
+
+    /// 获取日志类型
+    pub fn log_type(&self) -> LogType {
+        LogType::from(self.type_.as_str())
+    }
+}
+
+/// 服务器日志类型，对应 [`Log::type_`]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum LogType {
+    /// 普通操作日志
+    Simple,
+    /// 登录日志
+    Login,
+    /// 积分变动日志
+    Point,
+    /// 其他类型，保留原始字符串
+    Other(String),
+}
+
+impl LogType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            LogType::Simple => "simple",
+            LogType::Login => "login",
+            LogType::Point => "point",
+            LogType::Other(s) => s,
+        }
+    }
+}
+
+impl From<&str> for LogType {
+    fn from(s: &str) -> Self {
+        match s.to_ascii_lowercase().as_str() {
+            "simple" => LogType::Simple,
+            "login" => LogType::Login,
+            "point" => LogType::Point,
+            _ => LogType::Other(s.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for LogType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LoginData;
+
+    #[test]
+    fn from_hashed_does_not_hash_again() {
+        let plain = LoginData::new("user", "password", None);
+        let hashed = LoginData::from_hashed("user", &plain.password, None);
+        assert_eq!(plain.password, "5f4dcc3b5aa765d61d8327deb882cf99");
+        assert_eq!(hashed.password, plain.password);
+    }
 }
@@ -1,10 +1,15 @@
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
 use serde::Deserializer;
 use serde_json::Value;
 
 use crate::model::article::ArticleTag;
 use crate::model::bool_from_int;
-use crate::{impl_str_enum, utils::error::Error};
+use crate::model::user::UserId;
+use crate::{
+    impl_str_enum,
+    utils::{error::Error, parse_time},
+};
 
 fn bool_from_int_or_bool<'de, D>(deserializer: D) -> Result<bool, D::Error>
 where
@@ -181,7 +186,7 @@ pub struct NoticePoint {
     /// 数据ID
     pub dataId: String,
     /// 用户ID
-    pub userId: String,
+    pub userId: UserId,
     /// 数据类型
     pub dataType: u32,
     /// 通知描述
@@ -197,6 +202,11 @@ impl NoticePoint {
         serde_json::from_value(data.clone())
             .map_err(|e| Error::Parse(format!("Failed to parse NoticePoint: {}", e)))
     }
+
+    /// 通知创建时间，无法解析时返回 `None`
+    pub fn create_datetime(&self) -> Option<DateTime<Utc>> {
+        parse_time(&self.createTime)
+    }
 }
 
 /// 评论/回帖通知
@@ -371,6 +381,59 @@ impl NoticeFollow {
     }
 }
 
+/// 同城广播通知，同城用户发布的文章
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+#[allow(non_snake_case)]
+pub struct NoticeBroadcast {
+    /// 通知 Id
+    pub oId: String,
+    /// 数据 Id，即文章 Id
+    pub dataId: String,
+    /// 用户 Id
+    pub userId: UserId,
+    /// 数据类型
+    pub dataType: u32,
+    /// 文章地址
+    pub url: String,
+    /// 文章标题
+    #[serde(rename = "articleTitle")]
+    pub title: String,
+    /// 作者
+    #[serde(rename = "authorName")]
+    pub author: String,
+    /// 作者头像
+    pub thumbnailURL: String,
+    /// 通知内容
+    pub content: String,
+    /// 文章类型
+    #[serde(rename = "articleType")]
+    pub type_: u32,
+    /// 文章标签
+    #[serde(rename = "articleTags")]
+    pub tags: String,
+    /// 文章标签列表
+    #[serde(rename = "articleTagObjs")]
+    pub tagObjs: Vec<ArticleTag>,
+    /// 文章评论数
+    #[serde(rename = "articleCommentCount")]
+    pub commentCnt: u32,
+    /// 是否精选
+    #[serde(rename = "articlePerfect", deserialize_with = "bool_from_int")]
+    pub perfect: bool,
+    /// 是否已读
+    pub hasRead: bool,
+    /// 创建时间
+    pub createTime: String,
+}
+
+impl NoticeBroadcast {
+    pub fn from_value(data: &Value) -> Result<Self, Error> {
+        serde_json::from_value(data.clone())
+            .map_err(|e| Error::Parse(format!("Failed to parse NoticeBroadcast: {}", e)))
+    }
+}
+
 /// 系统通知数据
 #[derive(Clone, Debug, Deserialize)]
 #[allow(non_snake_case)]
@@ -378,7 +441,7 @@ pub struct NoticeSystem {
     /// 消息的 oId
     pub oId: String,
     /// 用户 Id
-    pub userId: String,
+    pub userId: UserId,
     /// 数据 Id
     pub dataId: String,
     /// 数据类型
@@ -425,7 +488,7 @@ pub struct NoticeMsg {
     /// 通知类型
     pub command: String,
     /// 通知接收者用户Id
-    pub userId: String,
+    pub userId: UserId,
     /// 全局公告内容，仅 `warnBroadcast` 有信息
     #[serde(rename = "warnBroadcastText")]
     pub content: Option<String>,
@@ -453,12 +516,51 @@ pub enum NoticeItem {
     At(NoticeAt),
     /// 关注通知
     Follow(NoticeFollow),
+    /// 同城广播通知
+    Broadcast(NoticeBroadcast),
     /// 系统通知数据
     System(NoticeSystem),
 }
 
 pub type NoticeList = Vec<NoticeItem>;
 
+/// 通知列表的一页
+#[derive(Clone, Debug, Default)]
+pub struct NoticePage {
+    /// 本页通知
+    pub list: NoticeList,
+    /// 页码
+    pub page: u32,
+    /// 每页数量
+    pub size: u32,
+    /// 总页数，仅服务端返回分页信息时有值
+    pub page_count: Option<u32>,
+    /// 是否还有下一页
+    pub has_more: bool,
+}
+
+impl NoticePage {
+    /// 构造分页结果，有总页数时据此判断是否还有下一页，否则根据本页数量推断
+    ///
+    /// - `list` 本页通知
+    /// - `page` 页码
+    /// - `size` 每页数量
+    /// - `page_count` 总页数（可选）
+    pub fn new(list: NoticeList, page: u32, size: u32, page_count: Option<u32>) -> Self {
+        let has_more = match page_count {
+            Some(count) => page < count,
+            None => size > 0 && list.len() >= size as usize,
+        };
+        Self {
+            list,
+            page,
+            size,
+            page_count,
+            has_more,
+        }
+    }
+}
+
 impl NoticeItem {
     pub fn from_value(data: &Value, notice_type: &NoticeType) -> Result<Self, Error> {
         match notice_type {
@@ -467,8 +569,35 @@ impl NoticeItem {
             NoticeType::Reply => Ok(NoticeItem::Reply(NoticeReply::from_value(data)?)),
             NoticeType::At => Ok(NoticeItem::At(NoticeAt::from_value(data)?)),
             NoticeType::Following => Ok(NoticeItem::Follow(NoticeFollow::from_value(data)?)),
+            NoticeType::Broadcast => Ok(NoticeItem::Broadcast(NoticeBroadcast::from_value(data)?)),
             NoticeType::System => Ok(NoticeItem::System(NoticeSystem::from_value(data)?)),
-            _ => Err(Error::Parse("Unsupported notice type".to_string())),
         }
     }
+
+    /// 是否已读
+    pub fn has_read(&self) -> bool {
+        match self {
+            NoticeItem::Point(n) => n.hasRead,
+            NoticeItem::Comment(n) => n.hasRead,
+            NoticeItem::Reply(n) => n.hasRead,
+            NoticeItem::At(n) => n.hasRead,
+            NoticeItem::Follow(n) => n.hasRead,
+            NoticeItem::Broadcast(n) => n.hasRead,
+            NoticeItem::System(n) => n.hasRead,
+        }
+    }
+
+    /// 通知创建时间，无法解析时返回 `None`
+    pub fn create_datetime(&self) -> Option<DateTime<Utc>> {
+        let time = match self {
+            NoticeItem::Point(n) => &n.createTime,
+            NoticeItem::Comment(n) => &n.createTime,
+            NoticeItem::Reply(n) => &n.createTime,
+            NoticeItem::At(n) => &n.createTime,
+            NoticeItem::Follow(n) => &n.createTime,
+            NoticeItem::Broadcast(n) => &n.createTime,
+            NoticeItem::System(n) => &n.createTime,
+        };
+        parse_time(time)
+    }
 }
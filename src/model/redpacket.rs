@@ -2,6 +2,7 @@ use std::str::FromStr;
 
 use serde_json::Value;
 
+use crate::model::user::UserId;
 use crate::{impl_str_enum, utils::error::Error};
 
 /// 猜拳类型
@@ -53,7 +54,7 @@ pub struct RedPacket {
 #[allow(non_snake_case)]
 pub struct RedPacketGot {
     /// 用户ID
-    pub userId: String,
+    pub userId: UserId,
     /// 用户名
     pub userName: String,
     /// 用户头像
@@ -70,6 +71,8 @@ pub struct RedPacketGot {
 pub struct RedPacketMessage {
     /// 消息类型，固定为redPacket
     pub msgType: String,
+    /// 红包类型
+    pub r#type: Option<RedPacketType>,
     /// 红包数
     pub count: u32,
     /// 领取数
@@ -114,16 +117,112 @@ pub struct RedPacketInfo {
     pub who: Vec<RedPacketGot>,
 }
 
-fn parse_string_list(data: &Value, primary_key: &str, fallback_key: &str) -> Vec<String> {
-    data.get(primary_key)
-        .or_else(|| data.get(fallback_key))
-        .and_then(|v| v.as_array())
-        .map(|arr| {
-            arr.iter()
-                .filter_map(|v| v.as_str().map(ToString::to_string))
-                .collect()
+/// 打开红包的结果类型
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RedPacketOutcome {
+    /// 抢到了红包（拼手气、平分、心跳、专属红包）
+    Got,
+    /// 没有抢到，红包已被领完
+    Missed,
+    /// 猜拳赢了
+    Win,
+    /// 猜拳输了，积分被扣除
+    Lose,
+    /// 猜拳平局
+    Tie,
+    /// 专属红包，当前用户不在接收者中
+    NotRecipient,
+    /// 无法确定当前用户，结果未知
+    Unknown,
+}
+
+/// 打开红包的结果
+#[derive(Clone, Debug)]
+pub struct RedPacketOpenResult {
+    /// 红包信息
+    pub info: RedPacketInfo,
+    /// 结果类型
+    pub outcome: RedPacketOutcome,
+    /// 当前用户获得的积分，猜拳输了时为负数；没有领取记录时为 `None`
+    pub amount: Option<i64>,
+}
+
+impl RedPacketOpenResult {
+    /// 从打开红包的响应中读取结果
+    ///
+    /// - `data` 响应 JSON
+    /// - `user_name` 当前用户名，用于在领取记录中查找自己；为 `None` 时结果为 [`RedPacketOutcome::Unknown`]
+    pub fn from_value(data: &Value, user_name: Option<&str>) -> Result<Self, Error> {
+        let info = RedPacketInfo::from_value(data)?;
+        let Some(user_name) = user_name else {
+            return Ok(Self {
+                info,
+                outcome: RedPacketOutcome::Unknown,
+                amount: None,
+            });
+        };
+
+        // 领取记录中的 userMoney 可能为负数（猜拳输了），这里直接读取原始值
+        let amount = data["who"].as_array().and_then(|who| {
+            who.iter()
+                .find(|item| {
+                    item["userName"]
+                        .as_str()
+                        .is_some_and(|name| name.eq_ignore_ascii_case(user_name))
+                })
+                .map(|item| {
+                    let money = item.get("userMoney").or_else(|| item.get("money"));
+                    money
+                        .and_then(|v| v.as_i64().or_else(|| v.as_str()?.parse().ok()))
+                        .unwrap_or(0)
+                })
+        });
+
+        let not_recipient = !info.recivers.is_empty()
+            && !info
+                .recivers
+                .iter()
+                .any(|name| name.eq_ignore_ascii_case(user_name));
+        let outcome = match amount {
+            _ if not_recipient => RedPacketOutcome::NotRecipient,
+            None => RedPacketOutcome::Missed,
+            Some(money) if info.info.gesture.is_some() => match money {
+                m if m > 0 => RedPacketOutcome::Win,
+                m if m < 0 => RedPacketOutcome::Lose,
+                _ => RedPacketOutcome::Tie,
+            },
+            Some(_) => RedPacketOutcome::Got,
+        };
+
+        Ok(Self {
+            info,
+            outcome,
+            amount,
         })
-        .unwrap_or_default()
+    }
+}
+
+/// 解析字符串列表，兼容数组、JSON 编码的数组字符串与逗号分隔字符串
+fn parse_string_list(data: &Value, primary_key: &str, fallback_key: &str) -> Vec<String> {
+    let strings = |arr: &[Value]| {
+        arr.iter()
+            .filter_map(|v| v.as_str().map(ToString::to_string))
+            .collect()
+    };
+
+    match data.get(primary_key).or_else(|| data.get(fallback_key)) {
+        Some(Value::Array(arr)) => strings(arr),
+        Some(Value::String(s)) => match serde_json::from_str::<Value>(s) {
+            Ok(Value::Array(arr)) => strings(&arr),
+            _ => s
+                .split(',')
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(ToString::to_string)
+                .collect(),
+        },
+        _ => Vec::new(),
+    }
 }
 
 fn parse_gesture(
@@ -132,10 +231,17 @@ fn parse_gesture(
     fallback_key: &str,
     err_ctx: &str,
 ) -> Result<Option<GestureType>, Error> {
-    let gesture = data
-        .get(primary_key)
-        .and_then(|v| v.as_str())
-        .or_else(|| data.get(fallback_key).and_then(|v| v.as_str()));
+    let value = data.get(primary_key).or_else(|| data.get(fallback_key));
+    // 猜拳红包的出拳也可能以数字 0/1/2 给出
+    if let Some(n) = value.and_then(|v| v.as_u64()) {
+        return match n {
+            0 => Ok(Some(GestureType::Rock)),
+            1 => Ok(Some(GestureType::Scissors)),
+            2 => Ok(Some(GestureType::Paper)),
+            _ => Err(Error::Parse(format!("Invalid gesture in {}", err_ctx))),
+        };
+    }
+    let gesture = value.and_then(|v| v.as_str());
 
     match gesture {
         Some(gesture_str) => GestureType::from_str(gesture_str)
@@ -165,10 +271,7 @@ fn parse_who_list(data: &Value) -> Result<Vec<RedPacketGot>, Error> {
             })
             .unwrap_or(0) as u32;
         got_list.push(RedPacketGot {
-            userId: item["userId"]
-                .as_str()
-                .unwrap_or("")
-                .to_string(),
+            userId: UserId::from_value(&item["userId"]),
             userName: item["userName"]
                 .as_str()
                 .unwrap_or("")
@@ -195,6 +298,7 @@ pub struct RedPacketStatusMsg {
     pub oId: String,
     pub count: u32,
     pub got: u32,
+    /// 发送者用户名（不是用户 Id）
     pub whoGive: String,
     pub whoGot: Vec<String>,
     pub avatarURL20: String,
@@ -316,6 +420,9 @@ impl RedPacketMessage {
                 .as_str()
                 .ok_or_else(|| Error::Parse("Missing senderId in RedPacketMessage".to_string()))?
                 .to_string(),
+            r#type: data["type"]
+                .as_str()
+                .and_then(|t| RedPacketType::from_str(t).ok()),
             GestureType: parse_gesture(data, "gesture", "GestureType", "RedPacketMessage")?,
             recivers: parse_string_list(data, "recivers", "receivers"),
             who: parse_who_list(data)?,
@@ -363,6 +470,26 @@ impl RedPacketInfo {
             who,
         })
     }
+
+    /// 由聊天室中的红包消息构造红包信息，不需要打开红包
+    ///
+    /// - `msg` 红包消息内容 [RedPacketMessage]
+    /// - `user_name` 发送者用户名
+    /// - `avatar` 发送者头像
+    pub fn from_message(msg: &RedPacketMessage, user_name: &str, avatar: &str) -> Self {
+        RedPacketInfo {
+            info: RedPacketBase {
+                count: msg.count,
+                gesture: msg.GestureType.clone(),
+                got: msg.got,
+                msg: msg.msg.clone(),
+                userName: user_name.to_string(),
+                userAvatarURL: avatar.to_string(),
+            },
+            recivers: msg.recivers.clone(),
+            who: msg.who.clone(),
+        }
+    }
 }
 
 impl_str_enum!(GestureType {
@@ -378,3 +505,56 @@ impl_str_enum!(RedPacketType {
     Heartbeat => "heartbeat",
     RockPaperScissors => "rockPaperScissors",
 });
+
+#[cfg(test)]
+mod tests {
+    use super::{RedPacketInfo, RedPacketMessage, RedPacketOpenResult, RedPacketOutcome};
+    use serde_json::json;
+
+    fn open_response(gesture: Option<u8>, recivers: &[&str], money: i64) -> serde_json::Value {
+        json!({
+            "info": {
+                "count": 1, "got": 1, "msg": "", "userName": "sender",
+                "userAvatarURL": "", "gesture": gesture
+            },
+            "recivers": recivers,
+            "who": [{ "userId": "1", "userName": "me", "avatar": "", "userMoney": money, "time": "" }]
+        })
+    }
+
+    #[test]
+    fn open_result_reports_gesture_outcome() {
+        let data = open_response(Some(0), &[], -32);
+        let result = RedPacketOpenResult::from_value(&data, Some("me")).expect("should parse");
+        assert_eq!(result.outcome, RedPacketOutcome::Lose);
+        assert_eq!(result.amount, Some(-32));
+
+        let data = open_response(Some(2), &[], 0);
+        let result = RedPacketOpenResult::from_value(&data, Some("me")).expect("ok");
+        assert_eq!(result.outcome, RedPacketOutcome::Tie);
+
+        let data = open_response(None, &["other"], 10);
+        let result = RedPacketOpenResult::from_value(&data, Some("me")).expect("ok");
+        assert_eq!(result.outcome, RedPacketOutcome::NotRecipient);
+
+        let result = RedPacketOpenResult::from_value(&data, Some("nobody")).expect("ok");
+        assert_eq!(result.amount, None);
+        let result = RedPacketOpenResult::from_value(&data, None).expect("ok");
+        assert_eq!(result.outcome, RedPacketOutcome::Unknown);
+    }
+
+    #[test]
+    fn info_from_message_keeps_who_list() {
+        let msg = RedPacketMessage::from_value(&json!({
+            "msgType": "redPacket", "count": 2, "got": 1, "money": 64, "msg": "摸鱼",
+            "senderId": "1", "type": "random",
+            "who": [{ "userId": "2", "userName": "me", "avatar": "", "userMoney": 20, "time": "" }]
+        }))
+        .expect("should parse");
+
+        let info = RedPacketInfo::from_message(&msg, "sender", "avatar.png");
+        assert_eq!(info.info.userName, "sender");
+        assert_eq!(info.info.got, 1);
+        assert_eq!(info.who[0].userMoney, 20);
+    }
+}
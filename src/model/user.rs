@@ -1,61 +1,138 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
 
-use crate::utils::error::Error;
+use crate::model::misc::UserLite;
+use crate::utils::{base_url, error::Error};
 
-#[derive(Clone, Serialize, Deserialize, Default)]
-#[allow(non_snake_case)]
+/// 用户 Id
+///
+/// 服务端不同接口中的 `userOId`、`oId`（用户信息）、`userId`、`fromId`/`toId` 均指同一个值，
+/// 且可能以字符串或数字返回，这里统一规范为字符串，便于跨事件关联同一用户。
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(transparent)]
+pub struct UserId(pub String);
+
+impl UserId {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self(id.into())
+    }
+
+    /// 从 JSON 值规范化，支持字符串与数字，其它类型为空字符串
+    pub fn from_value(value: &Value) -> Self {
+        match value {
+            Value::String(s) => Self(s.trim().to_string()),
+            Value::Number(n) => Self(n.to_string()),
+            _ => Self::default(),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<'de> Deserialize<'de> for UserId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value: Value = Deserialize::deserialize(deserializer)?;
+        Ok(UserId::from_value(&value))
+    }
+}
+
+impl std::fmt::Display for UserId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<String> for UserId {
+    fn from(id: String) -> Self {
+        Self(id)
+    }
+}
+
+impl From<&str> for UserId {
+    fn from(id: &str) -> Self {
+        Self(id.to_string())
+    }
+}
+
+impl PartialEq<str> for UserId {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for UserId {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
 pub struct UserInfo {
     /// 用户id
     #[serde(rename = "oId")]
-    oId: String,
+    pub o_id: UserId,
     /// 用户编号
-    userNo: String,
+    #[serde(rename = "userNo")]
+    pub user_no: String,
     /// 用户名
-    userName: String,
+    #[serde(rename = "userName")]
+    pub user_name: String,
     /// 用户昵称
-    userNickname: String,
+    #[serde(rename = "userNickname")]
+    pub user_nickname: String,
     /// 首页地址
     #[serde(rename = "userURL")]
-    URL: String,
+    pub url: String,
     /// 所在城市
     #[serde(rename = "userCity")]
-    city: String,
+    pub city: String,
     /// 签名
     #[serde(rename = "userIntro")]
-    intro: String,
+    pub intro: String,
     /// 是否在线
     #[serde(rename = "userOnlineFlag")]
-    online: bool,
+    pub online: bool,
     /// 用户积分
     #[serde(rename = "userPoint")]
-    points: i32,
+    pub points: i32,
     /// 用户组
     #[serde(rename = "userRole")]
-    role: String,
+    pub role: String,
     /// 角色
     #[serde(rename = "userAppRole")]
-    appRole: UserAppRole,
+    pub app_role: UserAppRole,
     /// 头像地址
     #[serde(rename = "userAvatarURL")]
-    avatar: String,
+    pub avatar: String,
     /// 用户卡片背景
-    cardBg: String,
+    #[serde(rename = "cardBg")]
+    pub card_bg: String,
     /// 用户关注数
     #[serde(rename = "followingUserCount")]
-    following: i32,
+    pub following: i32,
     /// 用户粉丝数
     #[serde(rename = "followerCount")]
-    follower: i32,
+    pub follower: i32,
     /// 在线时长(分钟)
     #[serde(rename = "onlineMinute")]
-    onlineMinutes: i32,
-    // / 是否已经关注，未登录则为 `hide`
-    // canFollow: String,
+    pub online_minutes: i32,
+    /// 是否已经关注，未登录则为 `hide`
+    #[serde(default, rename = "canFollow")]
+    pub can_follow: String,
     // / 用户所有勋章列表，包含未佩戴
     // ownedMetal: Vec<Metal>,
     /// 用户勋章列表
-    sysMetal: Vec<Metal>,
+    #[serde(rename = "sysMetal")]
+    pub sys_metal: Vec<Metal>,
     // / MBTI 性格类型
     // mbti: String,
 }
@@ -97,10 +174,11 @@ pub struct UpdateUserInfoParams {
     pub mbti: Option<String>,
 }
 
-#[derive(Clone, Serialize, Deserialize, Default)]
+/// 用户角色
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[repr(u8)]
 #[serde(try_from = "String")]
-enum UserAppRole {
+pub enum UserAppRole {
     /// 黑客
     #[default]
     Hack = 0,
@@ -154,13 +232,13 @@ impl std::fmt::Display for MetalAttrOrString {
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct Metal {
     /// 徽章基本信息
-    base: MetalBase,
+    pub base: MetalBase,
     /// 完整徽章地址（含文字）
-    url: String,
+    pub url: String,
     /// 徽章地址（不含文字）
-    icon: String,
+    pub icon: String,
     /// 是否佩戴
-    enable: bool,
+    pub enable: bool,
 }
 
 #[derive(Clone, Deserialize)]
@@ -210,24 +288,29 @@ impl TryFrom<String> for UserAppRole {
 }
 
 impl UserInfo {
+    /// 用户 Id
+    pub fn id(&self) -> &str {
+        self.o_id.as_str()
+    }
+
     pub fn name(&self) -> &str {
-        if self.userNickname.is_empty() {
-            &self.userName
+        if self.user_nickname.is_empty() {
+            &self.user_name
         } else {
-            &self.userNickname
+            &self.user_nickname
         }
     }
 
     pub fn username(&self) -> &str {
-        &self.userName
+        &self.user_name
     }
 
     pub fn user_no(&self) -> &str {
-        &self.userNo
+        &self.user_no
     }
 
     pub fn nickname(&self) -> &str {
-        &self.userNickname
+        &self.user_nickname
     }
 
     pub fn intro(&self) -> &str {
@@ -239,7 +322,7 @@ impl UserInfo {
     }
 
     pub fn url(&self) -> &str {
-        &self.URL
+        &self.url
     }
 
     pub fn avatar(&self) -> &str {
@@ -263,7 +346,38 @@ impl UserInfo {
     }
 
     pub fn online_minutes(&self) -> i32 {
-        self.onlineMinutes
+        self.online_minutes
+    }
+
+    /// 当前登录用户是否已关注该用户
+    pub fn is_following(&self) -> bool {
+        self.can_follow == "yes"
+    }
+}
+
+/// 两个用户之间的关注关系
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Relationship {
+    /// 互不关注
+    NotFollowing,
+    /// 我关注了对方
+    Following,
+    /// 对方关注了我
+    FollowedBy,
+    /// 互相关注
+    Mutual,
+    /// 对方就是自己
+    Oneself,
+}
+
+impl Relationship {
+    pub fn from_flags(following: bool, followed_by: bool) -> Self {
+        match (following, followed_by) {
+            (true, true) => Relationship::Mutual,
+            (true, false) => Relationship::Following,
+            (false, true) => Relationship::FollowedBy,
+            (false, false) => Relationship::NotFollowing,
+        }
     }
 }
 
@@ -281,7 +395,6 @@ trait MetalCommon {
     fn description(&self) -> &str;
     fn data(&self) -> &str;
     fn to_url(&self, include_text: bool) -> String {
-        let domain = "fishpi.cn";
         let attr_str = match self.attr() {
             MetalAttrOrString::Attr(attr) => {
                 format!(
@@ -296,7 +409,7 @@ trait MetalCommon {
         } else {
             "".to_string()
         };
-        format!("https://{}/gen?txt={}&{}", domain, text_str, attr_str)
+        format!("{}/gen?txt={}&{}", base_url(), text_str, attr_str)
     }
 }
 
@@ -433,3 +546,135 @@ impl UserPoint {
             .map_err(|e| Error::Parse(format!("Failed to parse UserPoint: {}", e)))
     }
 }
+
+/// 关注/粉丝列表的一页
+#[derive(Clone, Debug, Default)]
+pub struct FollowList {
+    /// 本页用户
+    pub users: Vec<UserLite>,
+    /// 页码
+    pub page: u32,
+    /// 每页数量
+    pub size: u32,
+    /// 对方设置了隐藏该列表，此时 `users` 为空
+    pub hidden: bool,
+}
+
+impl FollowList {
+    /// 对方隐藏了列表时的结果
+    pub fn hidden(page: u32, size: u32) -> Self {
+        Self {
+            page,
+            size,
+            hidden: true,
+            ..Self::default()
+        }
+    }
+
+    /// 是否可能还有下一页
+    pub fn has_more(&self) -> bool {
+        !self.hidden && self.size > 0 && self.users.len() >= self.size as usize
+    }
+}
+
+/// 转账结果
+///
+/// 转账接口成功时只返回 `code` 与 `msg`，余额变化请通过 [`crate::api::user::User::get_points`] 查询
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TransferResult {
+    /// 服务端返回的提示信息
+    pub msg: String,
+}
+
+impl TransferResult {
+    /// 从转账成功的响应中读取结果
+    ///
+    /// - `data` 响应 JSON
+    pub fn from_value(data: &Value) -> Self {
+        Self {
+            msg: data["msg"].as_str().unwrap_or_default().to_string(),
+        }
+    }
+}
+
+/// 聊天室每日活跃奖励（摸鱼打卡）的领取结果
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LivenessReward {
+    /// 本次领取到的积分，已领取过时为 0
+    pub points: u32,
+    /// 当前活跃度，即今日累计的摸鱼时长折算值
+    pub liveness: u32,
+    /// 今日是否已经领取过
+    pub already_claimed: bool,
+}
+
+/// 签到状态
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CheckinStatus {
+    /// 当前连续签到天数
+    pub current_streak: u64,
+    /// 最长连续签到天数
+    pub longest_streak: u64,
+    /// 今日是否已签到
+    pub checked_in_today: bool,
+}
+
+impl CheckinStatus {
+    /// 从用户信息原始数据中读取连续签到天数，服务端未返回的字段视为 0
+    ///
+    /// - `data` 用户信息 JSON
+    /// - `checked_in_today` 今日是否已签到
+    pub fn from_value(data: &Value, checked_in_today: bool) -> Self {
+        let streak = |key: &str| {
+            let v = &data[key];
+            v.as_u64()
+                .or_else(|| v.as_str().and_then(|s| s.parse().ok()))
+                .unwrap_or(0)
+        };
+        Self {
+            current_streak: streak("currentCheckinStreak"),
+            longest_streak: streak("longestCheckinStreak"),
+            checked_in_today,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CheckinStatus, MetalAttrOrString, MetalBase, MetalCommon, TransferResult};
+    use serde_json::json;
+
+    #[test]
+    fn transfer_result_reads_message() {
+        let result = TransferResult::from_value(&json!({ "code": 0, "msg": "转账成功" }));
+        assert_eq!(result.msg, "转账成功");
+        assert_eq!(
+            TransferResult::from_value(&json!({ "code": 0 })),
+            TransferResult::default()
+        );
+    }
+
+    #[test]
+    fn checkin_status_reads_streaks() {
+        let data = json!({ "currentCheckinStreak": 12, "longestCheckinStreak": "30" });
+        let status = CheckinStatus::from_value(&data, true);
+        assert_eq!(status.current_streak, 12);
+        assert_eq!(status.longest_streak, 30);
+        assert!(status.checked_in_today);
+
+        let status = CheckinStatus::from_value(&json!({}), false);
+        assert_eq!(status, CheckinStatus::default());
+    }
+
+    #[test]
+    fn metal_url_is_absolute() {
+        let metal = MetalBase {
+            attr: MetalAttrOrString::Str("ver=0.1&scale=0.79".to_string()),
+            name: "摸鱼派".to_string(),
+            ..Default::default()
+        };
+        let url = metal.to_url(true);
+        assert!(url.starts_with("https://fishpi.cn/gen?txt="), "{}", url);
+        assert_eq!(url, "https://fishpi.cn/gen?txt=摸鱼派&ver=0.1&scale=0.79");
+    }
+}
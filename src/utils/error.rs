@@ -1,3 +1,4 @@
+use serde_json::Value;
 use std::error::Error as StdError;
 use thiserror::Error;
 
@@ -5,8 +6,172 @@ use thiserror::Error;
 pub enum Error {
     #[error("Request error: {0}")]
     Request(Box<dyn StdError + Send + Sync>),
-    #[error("API error: {0}")]
-    Api(String),
+    /// 接口返回失败，`code` 为响应中的错误码，SDK 本地产生或响应中没有错误码时为 `None`
+    #[error("API error{}: {msg}", fmt_code(code))]
+    Api { code: Option<i64>, msg: String },
+    /// 未登录或 API key 无效、已过期，需要重新登录
+    #[error("Unauthorized{}: {msg}", fmt_code(code))]
+    Unauthorized { code: Option<i64>, msg: String },
+    /// 请求超时，可按需重试或调大超时时间
+    #[error("Request timed out: {0}")]
+    Timeout(String),
     #[error("Parse error: {0}")]
     Parse(String),
+    /// 红包已领取过，重试打开红包时可视为成功
+    #[error("Red packet already claimed: {0}")]
+    RedPacketAlreadyClaimed(String),
+    /// 金手指 key 无效或已过期，需要检查配置的 key 而不是请求本身
+    #[error("Invalid gold finger key: {0}")]
+    InvalidFingerKey(String),
+    /// 积分余额不足
+    #[error("Insufficient points: {0}")]
+    InsufficientPoints(String),
+    /// 目标用户不存在
+    #[error("User not found: {0}")]
+    UserNotFound(String),
+    /// 账号开启了两步验证，需要填写（正确的）一次性密码后重新登录
+    #[error("MFA code required: {0}")]
+    MfaRequired(String),
+    /// API key 格式不合法，由本地校验发现，未发起请求
+    #[error("Invalid API key: {0}")]
+    InvalidApiKey(String),
+    /// 已经感谢过该文章或评论
+    #[error("Already thanked: {0}")]
+    AlreadyThanked(String),
+    /// 请求被 [`crate::utils::CancellationToken`] 取消
+    #[error("Request cancelled")]
+    Cancelled,
+    /// 本地文件读写失败
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl Error {
+    /// 构造没有服务端错误码的 API 错误
+    ///
+    /// - `msg` 错误信息
+    pub fn api(msg: impl Into<String>) -> Self {
+        Error::Api {
+            code: None,
+            msg: msg.into(),
+        }
+    }
+
+    /// 构造带错误码的 API 错误
+    ///
+    /// - `code` 错误码
+    /// - `msg` 错误信息
+    pub fn api_with_code(code: i64, msg: impl Into<String>) -> Self {
+        Error::Api {
+            code: Some(code),
+            msg: msg.into(),
+        }
+    }
+
+    /// 从失败的接口响应构造 API 错误
+    ///
+    /// 认证失败（未登录、API key 无效或过期）时返回 [`Error::Unauthorized`]，其余返回 [`Error::Api`]。
+    ///
+    /// - `resp` 响应 JSON，读取其中的 `code` 与 `msg`
+    /// - `fallback` 响应中没有 `msg` 时使用的错误信息
+    pub fn from_response(resp: &Value, fallback: &str) -> Self {
+        let code = resp.get("code").and_then(Value::as_i64);
+        let msg = resp.get("msg").and_then(Value::as_str).unwrap_or(fallback);
+        Self::from_code(code, msg)
+    }
+
+    /// 按错误码与错误信息构造 API 错误，用于错误码不在 `code` 字段的响应
+    ///
+    /// 认证失败时返回 [`Error::Unauthorized`]，其余返回 [`Error::Api`]。
+    ///
+    /// - `code` 错误码
+    /// - `msg` 错误信息
+    pub fn from_code(code: Option<i64>, msg: impl Into<String>) -> Self {
+        let msg = msg.into();
+        if is_unauthorized(code, &msg) {
+            return Error::Unauthorized { code, msg };
+        }
+        Error::Api { code, msg }
+    }
+
+    /// 接口错误码，非 [`Error::Api`] 或 [`Error::Unauthorized`]、或没有错误码时返回 `None`
+    pub fn code(&self) -> Option<i64> {
+        match self {
+            Error::Api { code, .. } | Error::Unauthorized { code, .. } => *code,
+            _ => None,
+        }
+    }
+
+    /// 是否为认证失败（未登录或 API key 失效），可据此决定是否重新登录
+    pub fn is_auth(&self) -> bool {
+        match self {
+            Error::Unauthorized { .. } => true,
+            Error::Api { code, msg } => is_unauthorized(*code, msg),
+            Error::Request(err) => err
+                .downcast_ref::<reqwest::Error>()
+                .and_then(reqwest::Error::status)
+                .is_some_and(|status| status == reqwest::StatusCode::UNAUTHORIZED),
+            _ => false,
+        }
+    }
+}
+
+/// 错误信息中的错误码部分，没有错误码时为空
+fn fmt_code(code: &Option<i64>) -> String {
+    code.map(|code| format!(" ({})", code)).unwrap_or_default()
+}
+
+/// 服务端未登录时返回的错误信息
+const UNAUTHORIZED_MSGS: &[&str] = &["未登录", "请先登录"];
+
+/// 根据错误码与错误信息判断是否为认证失败
+///
+/// 只认 401 错误码与已知的未登录提示，仅提到 apiKey 的其它错误（如参数格式错误）不算认证失败，
+/// 避免自动重新登录的调用方陷入循环。
+fn is_unauthorized(code: Option<i64>, msg: &str) -> bool {
+    code == Some(401) || msg == "401" || UNAUTHORIZED_MSGS.iter().any(|m| msg.contains(m))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Error;
+    use serde_json::json;
+
+    #[test]
+    fn api_error_keeps_response_code() {
+        let err = Error::from_response(&json!({ "code": 401, "msg": "401" }), "API error");
+        assert!(matches!(err, Error::Unauthorized { .. }));
+        assert_eq!(err.code(), Some(401));
+        assert!(err.is_auth());
+        assert_eq!(err.to_string(), "Unauthorized (401): 401");
+        let err = Error::from_response(&json!({ "code": -1, "msg": "请先登录" }), "API error");
+        assert!(matches!(err, Error::Unauthorized { code: Some(-1), .. }));
+
+        let err = Error::from_response(&json!({ "code": -1, "msg": "发送失败" }), "API error");
+        assert_eq!(err.code(), Some(-1));
+        assert_eq!(err.to_string(), "API error (-1): 发送失败");
+
+        // 本地错误与响应中缺少 code 时不伪造错误码，避免与服务端的 -1 混淆
+        let err = Error::from_response(&json!({}), "发送失败");
+        assert_eq!(err.code(), None);
+        assert!(!err.is_auth());
+        assert_eq!(err.to_string(), "API error: 发送失败");
+        assert_eq!(Error::api("bad data").code(), None);
+
+        let err = Error::Request("HTTP error: 401 Unauthorized".into());
+        assert!(!err.is_auth());
+    }
+
+    #[test]
+    fn api_key_mentions_are_not_unauthorized() {
+        let err = Error::from_response(
+            &json!({ "code": -1, "msg": "apiKey parameter format error" }),
+            "API error",
+        );
+        assert!(matches!(err, Error::Api { .. }));
+        assert!(!err.is_auth());
+        let err = Error::from_code(Some(-1), "API key 长度不正确");
+        assert!(!err.is_auth());
+        assert!(!Error::from_code(Some(403), "403").is_auth());
+    }
 }